@@ -1,10 +1,11 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use criterion::{AxisScale, PlotConfiguration};
-use rand::Rng;
+use rand::RngExt;
 
 use fnv::FnvHashMap;
 use ordered_vecmap::VecMap;
 use std::collections::{BTreeMap, HashMap};
+use std::hint::black_box;
 
 #[inline]
 pub fn map_collect<T, U, C>(iter: impl IntoIterator<Item = T>, f: impl FnMut(T) -> U) -> C
@@ -23,7 +24,7 @@ pub fn get_trivial(c: &mut Criterion) {
     for n in [3, 5, 7, 16, 32, 64, 128, 512] {
         let data = {
             let mut v: Vec<u64> = vec![0; n];
-            rand::rng().fill(&mut *v);
+            rand::rng().fill(&mut v);
             v
         };
 
@@ -64,7 +65,7 @@ pub fn get_nontrivial(c: &mut Criterion) {
     for n in [3, 5, 7, 16, 32, 64, 128, 512] {
         let data = {
             let mut v: Vec<u64> = vec![0; n];
-            rand::rng().fill(&mut *v);
+            rand::rng().fill(&mut v);
             v.into_iter().map(|x| x.to_string()).collect::<Vec<_>>()
         };
 
@@ -8,8 +8,109 @@
 
 extern crate alloc;
 
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt;
+
+mod merge;
+
 pub mod vecmap;
 pub mod vecset;
 
 pub use self::vecmap::VecMap;
 pub use self::vecset::VecSet;
+
+/// Defines the order of keys in a `VecMap` or `VecSet`.
+///
+/// `compare` orders a stored key against a query, which defaults to the key
+/// type itself. Implementations may carry state, such as a collation table.
+///
+/// Operations that combine two collections, such as set operations, joins and
+/// methods taking a `VecSet` of keys, walk both in step using only the
+/// comparator of `self`. Both collections must be ordered by equivalent
+/// comparators, or the results are unspecified. This is not checked, since
+/// comparators such as closures cannot be compared.
+pub trait Comparator<K: ?Sized, Q: ?Sized = K> {
+    fn compare(&self, key: &K, query: &Q) -> Ordering;
+}
+
+/// Orders a query against a stored key of type `K`.
+///
+/// Implement this for a borrowed form of a composite key to look it up
+/// without allocating. Any `Q: Ord` that `K` borrows as is already comparable.
+///
+/// Point lookups such as `get`, `contains_key` and `rank` accept any
+/// comparable query. `range`, `range_mut`, the other range methods and the
+/// bound-cursor constructors also require `K: Borrow<Q>`, so that the query
+/// type of a range like `..` is inferred. A query that is only `Comparable`
+/// cannot be used with them.
+///
+/// ```
+/// use core::cmp::Ordering;
+/// use ordered_vecmap::{Comparable, VecMap};
+///
+/// struct Name<'a>(&'a str, &'a str);
+///
+/// impl Comparable<(String, String)> for Name<'_> {
+///     fn compare(&self, key: &(String, String)) -> Ordering {
+///         (self.0, self.1).cmp(&(key.0.as_str(), key.1.as_str()))
+///     }
+/// }
+///
+/// let map = VecMap::from_iter([(("Ada".into(), "Lovelace".into()), 1815)]);
+/// assert_eq!(map.get(&Name("Ada", "Lovelace")), Some(&1815));
+/// ```
+pub trait Comparable<K: ?Sized> {
+    fn compare(&self, key: &K) -> Ordering;
+}
+
+impl<Q, K> Comparable<K> for Q
+where
+    Q: Ord + ?Sized,
+    K: Borrow<Q> + ?Sized,
+{
+    #[inline]
+    fn compare(&self, key: &K) -> Ordering {
+        Ord::cmp(self, key.borrow())
+    }
+}
+
+/// The natural order given by `Ord`, which accepts any `Comparable` query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Natural;
+
+impl<K, Q> Comparator<K, Q> for Natural
+where
+    K: ?Sized,
+    Q: Comparable<K> + ?Sized,
+{
+    #[inline]
+    fn compare(&self, key: &K, query: &Q) -> Ordering {
+        query.compare(key).reverse()
+    }
+}
+
+impl<K, Q, F> Comparator<K, Q> for F
+where
+    K: ?Sized,
+    Q: ?Sized,
+    F: Fn(&K, &Q) -> Ordering,
+{
+    #[inline]
+    fn compare(&self, key: &K, query: &Q) -> Ordering {
+        self(key, query)
+    }
+}
+
+/// Error returned by cursor insertions that would break the sort order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnorderedKeyError;
+
+impl fmt::Display for UnorderedKeyError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("key is not properly ordered relative to neighbors")
+    }
+}
+
+impl core::error::Error for UnorderedKeyError {}
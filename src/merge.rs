@@ -0,0 +1,307 @@
+use crate::vecmap::EitherOrBoth;
+use crate::Comparator;
+
+use core::cmp::Ordering;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ptr;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Selects the keys that a merge walk compares for the elements of each side.
+pub(crate) trait Project<L, R> {
+    type Key;
+
+    fn left(elem: &L) -> &Self::Key;
+
+    fn right(elem: &R) -> &Self::Key;
+}
+
+/// Compares set elements as a whole.
+pub(crate) struct Whole;
+
+impl<T> Project<T, T> for Whole {
+    type Key = T;
+
+    #[inline]
+    fn left(elem: &T) -> &T {
+        elem
+    }
+
+    #[inline]
+    fn right(elem: &T) -> &T {
+        elem
+    }
+}
+
+/// Compares map entries by key.
+pub(crate) struct First;
+
+impl<K, V, W> Project<(K, V), (K, W)> for First {
+    type Key = K;
+
+    #[inline]
+    fn left(elem: &(K, V)) -> &K {
+        &elem.0
+    }
+
+    #[inline]
+    fn right(elem: &(K, W)) -> &K {
+        &elem.0
+    }
+}
+
+/// Compares map entries by key against the elements of a key set.
+pub(crate) struct Lookup;
+
+impl<K, V> Project<(K, V), K> for Lookup {
+    type Key = K;
+
+    #[inline]
+    fn left(elem: &(K, V)) -> &K {
+        &elem.0
+    }
+
+    #[inline]
+    fn right(elem: &K) -> &K {
+        elem
+    }
+}
+
+/// Walks two sorted slices in step, pairing up elements with equal keys.
+///
+/// Iterating yields every element of both sides. The `next_*` methods skip
+/// ahead to the elements a narrower operation needs.
+pub(crate) struct MergeWalk<'a, L, R, P, C> {
+    lhs: &'a [L],
+    rhs: &'a [R],
+    cmp: &'a C,
+    project: PhantomData<P>,
+}
+
+impl<'a, L, R, P, C> MergeWalk<'a, L, R, P, C> {
+    #[inline]
+    pub(crate) fn new(lhs: &'a [L], rhs: &'a [R], cmp: &'a C) -> Self {
+        Self {
+            lhs,
+            rhs,
+            cmp,
+            project: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements left on each side.
+    #[inline]
+    pub(crate) fn remaining(&self) -> (usize, usize) {
+        (self.lhs.len(), self.rhs.len())
+    }
+}
+
+impl<'a, L, R, P, C> MergeWalk<'a, L, R, P, C>
+where
+    P: Project<L, R>,
+    C: Comparator<P::Key>,
+{
+    #[inline]
+    fn compare(&self, x: &L, y: &R) -> Ordering {
+        self.cmp.compare(P::left(x), P::right(y))
+    }
+
+    /// Returns the next pair of elements with equal keys.
+    #[inline]
+    pub(crate) fn next_both(&mut self) -> Option<(&'a L, &'a R)> {
+        while let ([x, xs @ ..], [y, ys @ ..]) = (self.lhs, self.rhs) {
+            match self.compare(x, y) {
+                Ordering::Less => self.lhs = xs,
+                Ordering::Greater => self.rhs = ys,
+                Ordering::Equal => {
+                    self.lhs = xs;
+                    self.rhs = ys;
+                    return Some((x, y));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the next left element, along with the right element of equal key.
+    #[inline]
+    pub(crate) fn next_left(&mut self) -> Option<(&'a L, Option<&'a R>)> {
+        let [x, xs @ ..] = self.lhs else {
+            return None;
+        };
+        self.lhs = xs;
+        while let [y, ys @ ..] = self.rhs {
+            match self.compare(x, y) {
+                Ordering::Greater => self.rhs = ys,
+                Ordering::Less => break,
+                Ordering::Equal => {
+                    self.rhs = ys;
+                    return Some((x, Some(y)));
+                }
+            }
+        }
+        Some((x, None))
+    }
+
+    /// Returns the next right element, along with the left element of equal key.
+    #[inline]
+    pub(crate) fn next_right(&mut self) -> Option<(Option<&'a L>, &'a R)> {
+        let [y, ys @ ..] = self.rhs else {
+            return None;
+        };
+        self.rhs = ys;
+        while let [x, xs @ ..] = self.lhs {
+            match self.compare(x, y) {
+                Ordering::Less => self.lhs = xs,
+                Ordering::Greater => break,
+                Ordering::Equal => {
+                    self.lhs = xs;
+                    return Some((Some(x), y));
+                }
+            }
+        }
+        Some((None, y))
+    }
+}
+
+impl<'a, L, R, P, C> Iterator for MergeWalk<'a, L, R, P, C>
+where
+    P: Project<L, R>,
+    C: Comparator<P::Key>,
+{
+    type Item = EitherOrBoth<&'a L, &'a R>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.lhs, self.rhs) {
+            ([x, xs @ ..], [y, ys @ ..]) => match self.compare(x, y) {
+                Ordering::Less => {
+                    self.lhs = xs;
+                    Some(EitherOrBoth::Left(x))
+                }
+                Ordering::Greater => {
+                    self.rhs = ys;
+                    Some(EitherOrBoth::Right(y))
+                }
+                Ordering::Equal => {
+                    self.lhs = xs;
+                    self.rhs = ys;
+                    Some(EitherOrBoth::Both(x, y))
+                }
+            },
+            ([x, xs @ ..], []) => {
+                self.lhs = xs;
+                Some(EitherOrBoth::Left(x))
+            }
+            ([], [y, ys @ ..]) => {
+                self.rhs = ys;
+                Some(EitherOrBoth::Right(y))
+            }
+            ([], []) => None,
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.remaining();
+        (l.max(r), l.checked_add(r))
+    }
+}
+
+impl<L, R, P, C> FusedIterator for MergeWalk<'_, L, R, P, C>
+where
+    P: Project<L, R>,
+    C: Comparator<P::Key>,
+{
+}
+
+impl<L, R, P, C> Clone for MergeWalk<'_, L, R, P, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.lhs, self.rhs, self.cmp)
+    }
+}
+
+/// Merges runs of operations into a sorted vector in place.
+///
+/// `ops` must be sorted so that `same_key` groups the operations on each key
+/// into one run. Each run is passed to `f` along with the element of that
+/// key, if any, and the element `f` returns takes its place. Runs are merged
+/// from the back into the reserved capacity, so no second buffer is needed.
+///
+/// If a callback panics, `vec` stays sorted. It keeps the elements merged so
+/// far and those not yet reached, but loses the element of the current run.
+pub(crate) fn merge_grouped<E, O>(
+    vec: &mut Vec<E>,
+    mut ops: Vec<O>,
+    mut cmp: impl FnMut(&E, &O) -> Ordering,
+    mut same_key: impl FnMut(&O, &O) -> bool,
+    mut f: impl FnMut(Option<E>, vec::Drain<'_, O>) -> Option<E>,
+) {
+    vec.reserve(ops.len());
+    let len = vec.len();
+    let end = len + ops.len();
+
+    // SAFETY: the guard owns the elements until it restores the length.
+    unsafe { vec.set_len(0) };
+    let mut guard = MergeGuard {
+        vec,
+        read: len,
+        write: end,
+        end,
+    };
+
+    while let Some(last) = ops.last() {
+        let mut start = ops.len() - 1;
+        while start > 0 && same_key(&ops[start - 1], last) {
+            start -= 1;
+        }
+
+        // SAFETY: `[0, read)` and `[write, end)` hold initialized elements.
+        // The gap `[read, write)` is at least as long as the remaining
+        // operations, and each run writes at most one element.
+        unsafe {
+            let base = guard.vec.as_mut_ptr();
+            while guard.read > 0 {
+                if cmp(&*base.add(guard.read - 1), &ops[start]).is_le() {
+                    break;
+                }
+                guard.read -= 1;
+                guard.write -= 1;
+                ptr::copy_nonoverlapping(base.add(guard.read), base.add(guard.write), 1);
+            }
+            let cur = match guard.read.checked_sub(1) {
+                Some(idx) if cmp(&*base.add(idx), &ops[start]).is_eq() => {
+                    guard.read = idx;
+                    Some(ptr::read(base.add(idx)))
+                }
+                _ => None,
+            };
+            if let Some(elem) = f(cur, ops.drain(start..)) {
+                guard.write -= 1;
+                ptr::write(base.add(guard.write), elem);
+            }
+        }
+    }
+}
+
+struct MergeGuard<'a, E> {
+    vec: &'a mut Vec<E>,
+    read: usize,
+    write: usize,
+    end: usize,
+}
+
+impl<E> Drop for MergeGuard<'_, E> {
+    #[inline]
+    fn drop(&mut self) {
+        let tail = self.end - self.write;
+        unsafe {
+            let base = self.vec.as_mut_ptr();
+            ptr::copy(base.add(self.write), base.add(self.read), tail);
+            self.vec.set_len(self.read + tail);
+        }
+    }
+}
@@ -1,35 +1,60 @@
+use crate::merge::{merge_grouped, First, Lookup, MergeWalk};
 use crate::vecset::VecSet;
+use crate::{Comparator, Natural, UnorderedKeyError};
 
 use core::borrow::Borrow;
 use core::cmp::Ordering;
 use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{self, FusedIterator};
 use core::mem;
+use core::ops::{Bound, RangeBounds};
 use core::ptr;
 use core::slice;
 
 use alloc::vec;
 use alloc::vec::Vec;
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub struct VecMap<K, V>(Vec<(K, V)>);
+/// A map stored as a vector of entries sorted by key.
+///
+/// Keys are ordered by the comparator `C`, which defaults to `Ord`.
+/// Operations on two maps, or on a map and a `VecSet` of keys, require
+/// both to be ordered by equivalent comparators; see [`Comparator`].
+#[derive(Clone)]
+pub struct VecMap<K, V, C = Natural>(Vec<(K, V)>, C);
 
 impl<K, V> VecMap<K, V> {
     #[inline]
     #[must_use]
     pub const fn new() -> Self {
-        Self(Vec::new())
+        Self(Vec::new(), Natural)
     }
 
     #[inline]
     #[must_use]
     pub fn from_single(key: K, value: V) -> Self {
-        Self(vec![(key, value)])
+        Self(vec![(key, value)], Natural)
     }
 
     #[inline]
     #[must_use]
     pub fn with_capacity(cap: usize) -> Self {
-        Self(Vec::with_capacity(cap))
+        Self(Vec::with_capacity(cap), Natural)
+    }
+}
+
+impl<K, V, C> VecMap<K, V, C> {
+    #[inline]
+    #[must_use]
+    pub const fn with_comparator(cmp: C) -> Self {
+        Self(Vec::new(), cmp)
+    }
+
+    /// Returns the comparator that orders the keys.
+    #[inline]
+    #[must_use]
+    pub fn comparator(&self) -> &C {
+        &self.1
     }
 
     #[inline]
@@ -56,6 +81,237 @@ impl<K, V> VecMap<K, V> {
         IterMut(self.0.as_mut_slice().iter_mut())
     }
 
+    /// Returns an iterator over the keys, in order.
+    #[inline]
+    #[must_use]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.0.as_slice().iter())
+    }
+
+    /// Returns an iterator over the values, in key order.
+    #[inline]
+    #[must_use]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.0.as_slice().iter())
+    }
+
+    /// Returns an iterator over mutable references to the values, in key order.
+    #[inline]
+    #[must_use]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.0.as_mut_slice().iter_mut())
+    }
+
+    /// Consumes the map and returns an iterator over its keys, in order.
+    #[inline]
+    #[must_use]
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys(self.0.into_iter())
+    }
+
+    /// Consumes the map and returns an iterator over its values, in key order.
+    #[inline]
+    #[must_use]
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues(self.0.into_iter())
+    }
+
+    /// Returns the entry with the smallest key.
+    #[inline]
+    #[must_use]
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.0.first().map(|(k, v)| (k, v))
+    }
+
+    /// Returns the entry with the greatest key.
+    #[inline]
+    #[must_use]
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.0.last().map(|(k, v)| (k, v))
+    }
+
+    /// Returns the entry with the smallest key for in-place manipulation.
+    #[inline]
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V, C>> {
+        if self.0.is_empty() {
+            return None;
+        }
+        Some(OccupiedEntry { map: self, idx: 0 })
+    }
+
+    /// Returns the entry with the greatest key for in-place manipulation.
+    #[inline]
+    pub fn last_entry(&mut self) -> Option<OccupiedEntry<'_, K, V, C>> {
+        let idx = self.0.len().checked_sub(1)?;
+        Some(OccupiedEntry { map: self, idx })
+    }
+
+    /// Removes and returns the entry with the smallest key.
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        if self.0.is_empty() {
+            return None;
+        }
+        Some(self.0.remove(0))
+    }
+
+    /// Removes and returns the entry with the greatest key.
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.0.pop()
+    }
+
+    /// Keeps only the entries for which `f` returns `true`.
+    #[inline]
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+        self.0.retain(|(k, v)| f(k, v));
+    }
+
+    /// Keeps only the entries for which `f` returns `true`, letting `f` modify the values.
+    #[inline]
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        self.0.retain_mut(|(k, v)| f(k, v));
+    }
+
+    /// Returns an iterator which removes and yields the entries matching `pred`.
+    ///
+    /// If the iterator is dropped before it is exhausted, the remaining
+    /// entries are retained.
+    #[inline]
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let old_len = self.0.len();
+        unsafe { self.0.set_len(0) };
+        ExtractIf {
+            vec: &mut self.0,
+            idx: 0,
+            del: 0,
+            old_len,
+            pred,
+        }
+    }
+
+    /// Replaces every value with the result of `f`, keeping the keys.
+    #[inline]
+    #[must_use]
+    pub fn map_values<U>(self, mut f: impl FnMut(&K, V) -> U) -> VecMap<K, U, C> {
+        let entries = self.0.into_iter().map(|(k, v)| {
+            let u = f(&k, v);
+            (k, u)
+        });
+        VecMap(entries.collect(), self.1)
+    }
+
+    /// Replaces every value with the result of `f`, removing the entries for
+    /// which it returns `None`.
+    #[inline]
+    #[must_use]
+    pub fn filter_map_values<U>(self, mut f: impl FnMut(&K, V) -> Option<U>) -> VecMap<K, U, C> {
+        let entries = self.0.into_iter().filter_map(|(k, v)| {
+            let u = f(&k, v)?;
+            Some((k, u))
+        });
+        VecMap(entries.collect(), self.1)
+    }
+
+    /// Returns the entry at position `idx` in key order.
+    #[inline]
+    #[must_use]
+    pub fn get_index(&self, idx: usize) -> Option<(&K, &V)> {
+        self.0.get(idx).map(|(k, v)| (k, v))
+    }
+
+    /// Returns the entry at position `idx` in key order, with a mutable value.
+    #[inline]
+    pub fn get_index_mut(&mut self, idx: usize) -> Option<(&K, &mut V)> {
+        self.0.get_mut(idx).map(|(k, v)| (&*k, v))
+    }
+
+    /// Removes the entry at `idx`, shifting the following entries down.
+    #[inline]
+    pub fn remove_index(&mut self, idx: usize) -> Option<(K, V)> {
+        (idx < self.0.len()).then(|| self.0.remove(idx))
+    }
+
+    /// Returns the lower median entry.
+    #[inline]
+    #[must_use]
+    pub fn median(&self) -> Option<(&K, &V)> {
+        let idx = self.len().checked_sub(1)? / 2;
+        let (k, v) = unsafe { self.at_unchecked(idx) };
+        Some((k, v))
+    }
+
+    /// Returns the entry at rank `floor(q * (len - 1))`.
+    ///
+    /// Returns `None` if the map is empty or `q` is not in `0.0..=1.0`,
+    /// which includes NaN.
+    #[inline]
+    #[must_use]
+    pub fn quantile(&self, q: f64) -> Option<(&K, &V)> {
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let last = self.len().checked_sub(1)?;
+        let idx = (q * last as f64) as usize;
+        let (k, v) = unsafe { self.at_unchecked(idx.min(last)) };
+        Some((k, v))
+    }
+
+    /// Binary searches the keys with `f`, which orders a key against the target.
+    #[inline]
+    pub fn binary_search_by(&self, mut f: impl FnMut(&K) -> Ordering) -> Result<usize, usize> {
+        self.0.binary_search_by(|probe| f(&probe.0))
+    }
+
+    /// Returns the entry whose key `f` orders as `Equal`, found by binary search.
+    #[inline]
+    pub fn get_by(&self, f: impl FnMut(&K) -> Ordering) -> Option<(&K, &V)> {
+        let idx = self.binary_search_by(f).ok()?;
+        let (k, v) = unsafe { self.at_unchecked(idx) };
+        Some((k, v))
+    }
+
+    /// Like [`get_by`](Self::get_by), but with a mutable value.
+    #[inline]
+    pub fn get_by_mut(&mut self, f: impl FnMut(&K) -> Ordering) -> Option<(&K, &mut V)> {
+        let idx = self.binary_search_by(f).ok()?;
+        let (k, v) = unsafe { self.at_unchecked_mut(idx) };
+        Some((k, v))
+    }
+
+    /// Returns the index of the first key for which `pred` is false.
+    ///
+    /// The keys must be partitioned by `pred`, true before false.
+    #[inline]
+    pub fn partition_point(&self, mut pred: impl FnMut(&K) -> bool) -> usize {
+        self.0.partition_point(|(k, _)| pred(k))
+    }
+
+    fn equal_range_by(&self, mut f: impl FnMut(&K) -> Ordering) -> (usize, usize) {
+        let start = self.partition_point(|k| f(k).is_lt());
+        let len = self.0[start..].partition_point(|(k, _)| f(k).is_le());
+        (start, start + len)
+    }
+
+    /// Returns the entries whose keys `f` maps to `Equal`.
+    ///
+    /// `f` must return `Less` below the range and `Greater` above it.
+    #[inline]
+    pub fn range_by(&self, f: impl FnMut(&K) -> Ordering) -> Range<'_, K, V> {
+        let (start, end) = self.equal_range_by(f);
+        Range(self.0[start..end].iter())
+    }
+
+    /// Like [`range_by`](Self::range_by), but with mutable values.
+    #[inline]
+    pub fn range_by_mut(&mut self, f: impl FnMut(&K) -> Ordering) -> RangeMut<'_, K, V> {
+        let (start, end) = self.equal_range_by(f);
+        RangeMut(self.0[start..end].iter_mut())
+    }
+
     unsafe fn at_unchecked(&self, idx: usize) -> &(K, V) {
         self.0.get_unchecked(idx)
     }
@@ -68,26 +324,140 @@ impl<K, V> VecMap<K, V> {
 impl<K: Ord, V> VecMap<K, V> {
     #[inline]
     #[must_use]
-    pub fn from_vec(mut v: Vec<(K, V)>) -> Self {
-        v.sort_unstable_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
-        v.dedup_by(|x, first| x.0 == first.0);
-        Self(v)
+    pub fn from_vec(v: Vec<(K, V)>) -> Self {
+        Self::from_vec_with_comparator(v, Natural)
+    }
+}
+
+impl<K, V, C: Comparator<K>> VecMap<K, V, C> {
+    /// Builds a map from entries in any order, ordered by `cmp`.
+    ///
+    /// If several entries have equal keys, only one of them is kept.
+    #[inline]
+    #[must_use]
+    pub fn from_vec_with_comparator(mut v: Vec<(K, V)>, cmp: C) -> Self {
+        v.sort_unstable_by(|lhs, rhs| cmp.compare(&lhs.0, &rhs.0));
+        v.dedup_by(|x, first| cmp.compare(&x.0, &first.0).is_eq());
+        Self(v, cmp)
+    }
+
+    /// Builds a map with the keys of `keys` and the values computed by `f`.
+    #[inline]
+    #[must_use]
+    pub fn from_set(keys: VecSet<K, C>, mut f: impl FnMut(&K) -> V) -> Self {
+        let (keys, cmp) = keys.into_parts();
+        let entries = keys.into_iter().map(|k| {
+            let v = f(&k);
+            (k, v)
+        });
+        Self(entries.collect(), cmp)
     }
 
     fn search<Q>(&self, key: &Q) -> Result<usize, usize>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        self.0
+            .binary_search_by(|probe| self.1.compare(&probe.0, key))
+    }
+
+    fn search_lower<Q>(&self, key: &Q) -> usize
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        match self.search(key) {
+            Ok(idx) | Err(idx) => idx,
+        }
+    }
+
+    fn search_upper<Q>(&self, key: &Q) -> usize
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        match self.search(key) {
+            Ok(idx) => idx + 1,
+            Err(idx) => idx,
+        }
+    }
+
+    fn search_start<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        match bound {
+            Bound::Included(key) => self.search_lower(key),
+            Bound::Excluded(key) => self.search_upper(key),
+            Bound::Unbounded => 0,
+        }
+    }
+
+    fn search_end<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        match bound {
+            Bound::Included(key) => self.search_upper(key),
+            Bound::Excluded(key) => self.search_lower(key),
+            Bound::Unbounded => self.len(),
+        }
+    }
+
+    fn search_range<Q, R>(&self, range: &R) -> (usize, usize)
+    where
+        C: Comparator<K, Q> + Comparator<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Excluded(s), Bound::Excluded(e)) if self.1.compare(s, e).is_eq() => {
+                panic!("range start and end are equal and excluded in VecMap")
+            }
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+                if self.1.compare(s, e).is_gt() =>
+            {
+                panic!("range start is greater than range end in VecMap")
+            }
+            _ => {}
+        }
+
+        let start = self.search_start(range.start_bound());
+        let end = self.search_end(range.end_bound());
+        (start, end.max(start))
+    }
+
+    /// Returns the position of `key` in key order, if it is present.
+    #[inline]
+    #[must_use]
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        self.search(key).ok()
+    }
+
+    /// Returns the number of keys less than `key`.
+    #[inline]
+    #[must_use]
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        self.0.binary_search_by(|probe| probe.0.borrow().cmp(key))
+        self.search_lower(key)
     }
 
     #[inline]
     #[must_use]
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         self.search(key).is_ok()
     }
@@ -96,8 +466,8 @@ impl<K: Ord, V> VecMap<K, V> {
     #[must_use]
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         let idx = self.search(key).ok()?;
         let entry = unsafe { self.at_unchecked(idx) };
@@ -108,90 +478,415 @@ impl<K: Ord, V> VecMap<K, V> {
     #[must_use]
     pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         let idx = self.search(key).ok()?;
         let entry = unsafe { self.at_unchecked_mut(idx) };
         Some(&mut entry.1)
     }
 
-    #[inline]
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        match self.search(&key) {
-            Ok(idx) => {
-                let entry = unsafe { self.at_unchecked_mut(idx) };
-                Some(mem::replace(&mut entry.1, value))
-            }
-            Err(idx) => {
-                self.0.insert(idx, (key, value));
-                None
-            }
+    fn floor_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        match self.search(key) {
+            Ok(idx) => Some(idx),
+            Err(idx) => idx.checked_sub(1),
+        }
+    }
+
+    fn ceiling_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        match self.search(key) {
+            Ok(idx) => Some(idx),
+            Err(idx) => (idx < self.0.len()).then_some(idx),
         }
     }
 
+    fn predecessor_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        match self.search(key) {
+            Ok(idx) | Err(idx) => idx.checked_sub(1),
+        }
+    }
+
+    fn successor_index<Q>(&self, key: &Q) -> Option<usize>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = match self.search(key) {
+            Ok(idx) => idx + 1,
+            Err(idx) => idx,
+        };
+        (idx < self.0.len()).then_some(idx)
+    }
+
+    /// Returns the entry with the greatest key less than or equal to `key`.
     #[inline]
-    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    #[must_use]
+    pub fn floor<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        let idx = self.search(key).ok()?;
-        let entry = self.0.remove(idx);
-        Some(entry.1)
+        self.get_index(self.floor_index(key)?)
     }
 
+    /// Like [`floor`](Self::floor), but with a mutable value.
     #[inline]
-    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
-        match self.search(&key) {
-            Ok(idx) => Entry::Occupied(OccupiedEntry { map: self, idx }),
-            Err(idx) => Entry::Vacant(VacantEntry {
-                map: self,
-                idx,
-                key,
-            }),
-        }
+    #[must_use]
+    pub fn floor_mut<Q>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.floor_index(key)?;
+        self.get_index_mut(idx)
     }
 
+    /// Returns the entry with the smallest key greater than or equal to `key`.
     #[inline]
-    pub fn merge_copied_with(&mut self, other: &Self, mut f: impl FnMut(V, V) -> V)
+    #[must_use]
+    pub fn ceiling<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
-        K: Copy,
-        V: Copy,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        let lhs = &mut self.0;
-        let rhs = &other.0;
+        self.get_index(self.ceiling_index(key)?)
+    }
 
-        let ans_cap = lhs.len().checked_add(rhs.len()).unwrap();
-        lhs.reserve(ans_cap);
+    /// Like [`ceiling`](Self::ceiling), but with a mutable value.
+    #[inline]
+    #[must_use]
+    pub fn ceiling_mut<Q>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.ceiling_index(key)?;
+        self.get_index_mut(idx)
+    }
 
-        unsafe {
-            let mut p1 = lhs.as_ptr();
-            let mut p2 = rhs.as_ptr();
-            let mut p3 = lhs.as_mut_ptr().add(lhs.len());
-            let e1 = p1.add(lhs.len());
-            let e2 = p2.add(rhs.len());
+    /// Returns the entry with the greatest key strictly less than `key`.
+    #[inline]
+    #[must_use]
+    pub fn predecessor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        self.get_index(self.predecessor_index(key)?)
+    }
 
-            while p1 < e1 && p2 < e2 {
-                let (k1, v1) = &*p1;
-                let (k2, v2) = &*p2;
-                match Ord::cmp(k1, k2) {
-                    Ordering::Less => {
-                        ptr::copy_nonoverlapping(p1, p3, 1);
-                        p1 = p1.add(1);
-                    }
-                    Ordering::Greater => {
-                        ptr::copy_nonoverlapping(p2, p3, 1);
-                        p2 = p2.add(1);
-                    }
-                    Ordering::Equal => {
-                        let v = f(*v1, *v2);
-                        p3.write((*k1, v));
-                        p1 = p1.add(1);
-                        p2 = p2.add(1);
-                    }
-                }
-                p3 = p3.add(1);
+    /// Like [`predecessor`](Self::predecessor), but with a mutable value.
+    #[inline]
+    #[must_use]
+    pub fn predecessor_mut<Q>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.predecessor_index(key)?;
+        self.get_index_mut(idx)
+    }
+
+    /// Returns the entry with the smallest key strictly greater than `key`.
+    #[inline]
+    #[must_use]
+    pub fn successor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        self.get_index(self.successor_index(key)?)
+    }
+
+    /// Like [`successor`](Self::successor), but with a mutable value.
+    #[inline]
+    #[must_use]
+    pub fn successor_mut<Q>(&mut self, key: &Q) -> Option<(&K, &mut V)>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.successor_index(key)?;
+        self.get_index_mut(idx)
+    }
+
+    /// Returns the position of the first entry whose key is not less than
+    /// `key`, along with that entry.
+    #[inline]
+    #[must_use]
+    pub fn lower_bound<Q>(&self, key: &Q) -> (usize, Option<(&K, &V)>)
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search_lower(key);
+        (idx, self.get_index(idx))
+    }
+
+    /// Returns the position of the first entry whose key is greater than
+    /// `key`, along with that entry.
+    #[inline]
+    #[must_use]
+    pub fn upper_bound<Q>(&self, key: &Q) -> (usize, Option<(&K, &V)>)
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search_upper(key);
+        (idx, self.get_index(idx))
+    }
+
+    /// Returns an iterator over the entries whose keys are in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    #[must_use]
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        C: Comparator<K, Q> + Comparator<Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        Range(self.0[start..end].iter())
+    }
+
+    /// Like [`range`](Self::range), but with mutable values.
+    #[inline]
+    #[must_use]
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        C: Comparator<K, Q> + Comparator<Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        RangeMut(self.0[start..end].iter_mut())
+    }
+
+    fn upper_bound_index<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        self.search_end(bound).checked_sub(1).unwrap_or(self.len())
+    }
+
+    /// Returns a cursor at the first entry above `bound`, or at the ghost
+    /// position if there is none.
+    #[inline]
+    #[must_use]
+    pub fn lower_bound_cursor<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, K, V>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+    {
+        let index = self.search_start(bound);
+        Cursor {
+            entries: &self.0,
+            index,
+        }
+    }
+
+    /// Like [`lower_bound_cursor`](Self::lower_bound_cursor), but with editing operations.
+    #[inline]
+    pub fn lower_bound_cursor_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, K, V, C>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+    {
+        let index = self.search_start(bound);
+        CursorMut {
+            entries: &mut self.0,
+            cmp: &self.1,
+            index,
+        }
+    }
+
+    /// Returns a cursor at the last entry below `bound`, or at the ghost
+    /// position if there is none.
+    #[inline]
+    #[must_use]
+    pub fn upper_bound_cursor<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, K, V>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+    {
+        let index = self.upper_bound_index(bound);
+        Cursor {
+            entries: &self.0,
+            index,
+        }
+    }
+
+    /// Like [`upper_bound_cursor`](Self::upper_bound_cursor), but with editing operations.
+    #[inline]
+    pub fn upper_bound_cursor_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, K, V, C>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+    {
+        let index = self.upper_bound_index(bound);
+        CursorMut {
+            entries: &mut self.0,
+            cmp: &self.1,
+            index,
+        }
+    }
+
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.search(&key) {
+            Ok(idx) => {
+                let entry = unsafe { self.at_unchecked_mut(idx) };
+                Some(mem::replace(&mut entry.1, value))
+            }
+            Err(idx) => {
+                self.0.insert(idx, (key, value));
+                None
+            }
+        }
+    }
+
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search(key).ok()?;
+        let entry = self.0.remove(idx);
+        Some(entry.1)
+    }
+
+    #[inline]
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, C> {
+        match self.search(&key) {
+            Ok(idx) => Entry::Occupied(OccupiedEntry { map: self, idx }),
+            Err(idx) => Entry::Vacant(VacantEntry {
+                map: self,
+                idx,
+                key,
+            }),
+        }
+    }
+
+    fn merge_sorted_vec(&mut self, rhs: Vec<(K, V)>, mut f: impl FnMut(&K, V, V) -> V) {
+        let cmp = &self.1;
+        merge_grouped(
+            &mut self.0,
+            rhs,
+            |x, y| cmp.compare(&x.0, &y.0),
+            |_, _| false,
+            |cur, mut group| match (cur, group.next()) {
+                (Some((k, v1)), Some((_, v2))) => {
+                    let v = f(&k, v1, v2);
+                    Some((k, v))
+                }
+                (None, entry) | (entry, None) => entry,
+            },
+        );
+    }
+
+    /// Moves all entries of `other` into `self`, combining the values of keys
+    /// present in both maps with `f(key, self_value, other_value)`.
+    ///
+    /// If `f` or the comparator panics, `self` stays sorted with only part of
+    /// `other` merged, and the entry passed to `f` is dropped.
+    #[inline]
+    pub fn merge_with(&mut self, other: Self, f: impl FnMut(&K, V, V) -> V) {
+        self.merge_sorted_vec(other.0, f);
+    }
+
+    /// Clones all entries of `other` into `self`, combining the values of keys
+    /// present in both maps with `f(key, self_value, other_value)`.
+    ///
+    /// If `f`, the comparator or `clone` panics, `self` stays sorted with only
+    /// part of `other` merged, and the entry passed to `f` is dropped.
+    #[inline]
+    pub fn merge_cloned_with(&mut self, other: &Self, mut f: impl FnMut(&K, V, V) -> V)
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let cmp = &self.1;
+        merge_grouped(
+            &mut self.0,
+            other.0.iter().collect(),
+            |x, y| cmp.compare(&x.0, &y.0),
+            |_, _| false,
+            |cur, mut group| match (cur, group.next()) {
+                (Some((k, v1)), Some((_, v2))) => {
+                    let v = f(&k, v1, v2.clone());
+                    Some((k, v))
+                }
+                (None, Some(entry)) => Some(entry.clone()),
+                (cur, None) => cur,
+            },
+        );
+    }
+
+    /// Copies all entries of `other` into `self`, combining the values of keys
+    /// present in both maps with `f(self_value, other_value)`.
+    #[inline]
+    pub fn merge_copied_with(&mut self, other: &Self, mut f: impl FnMut(V, V) -> V)
+    where
+        K: Copy,
+        V: Copy,
+    {
+        let lhs = &mut self.0;
+        let rhs = &other.0;
+
+        let ans_cap = lhs.len().checked_add(rhs.len()).unwrap();
+        lhs.reserve(ans_cap);
+
+        unsafe {
+            let mut p1 = lhs.as_ptr();
+            let mut p2 = rhs.as_ptr();
+            let mut p3 = lhs.as_mut_ptr().add(lhs.len());
+            let e1 = p1.add(lhs.len());
+            let e2 = p2.add(rhs.len());
+
+            while p1 < e1 && p2 < e2 {
+                let (k1, v1) = &*p1;
+                let (k2, v2) = &*p2;
+                match self.1.compare(k1, k2) {
+                    Ordering::Less => {
+                        ptr::copy_nonoverlapping(p1, p3, 1);
+                        p1 = p1.add(1);
+                    }
+                    Ordering::Greater => {
+                        ptr::copy_nonoverlapping(p2, p3, 1);
+                        p2 = p2.add(1);
+                    }
+                    Ordering::Equal => {
+                        let v = f(*v1, *v2);
+                        p3.write((*k1, v));
+                        p1 = p1.add(1);
+                        p2 = p2.add(1);
+                    }
+                }
+                p3 = p3.add(1);
             }
             if p1 < e1 {
                 let cnt = e1.offset_from(p1) as usize;
@@ -216,218 +911,2018 @@ impl<K: Ord, V> VecMap<K, V> {
     #[inline]
     pub fn remove_less_than<Q>(&mut self, key: &Q)
     where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        let end = self.rank(key);
+        self.0.drain(..end);
+    }
+
+    /// Removes the entries whose keys are in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    pub fn remove_range<Q, R>(&mut self, range: R)
+    where
+        C: Comparator<K, Q> + Comparator<Q>,
+        Q: ?Sized,
         K: Borrow<Q>,
-        Q: Ord + ?Sized,
-    {
-        struct Guard<'a, K, V> {
-            v: &'a mut Vec<(K, V)>,
-            remove_cnt: usize,
-        }
-
-        impl<K, V> Drop for Guard<'_, K, V> {
-            fn drop(&mut self) {
-                let v = &mut *self.v;
-                let remove_cnt = self.remove_cnt;
-                let remain_cnt = v.len().wrapping_sub(remove_cnt);
-                unsafe {
-                    let dst = v.as_mut_ptr();
-                    let src = dst.add(remove_cnt);
-                    ptr::copy(src, dst, remain_cnt);
-                    v.set_len(remain_cnt)
-                }
-            }
-        }
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        self.0.drain(start..end);
+    }
 
-        let remove_cnt = match self.search(key) {
-            Ok(idx) => idx,
-            Err(idx) => idx,
-        };
-        if remove_cnt == 0 || remove_cnt >= self.0.len() {
-            return;
+    /// Removes the entries whose keys are in `range` and returns them as an iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    pub fn drain_range<Q, R>(&mut self, range: R) -> Drain<'_, K, V>
+    where
+        C: Comparator<K, Q> + Comparator<Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        Drain(self.0.drain(start..end))
+    }
+
+    /// Keeps only the entries whose keys are in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    pub fn retain_range<Q, R>(&mut self, range: R)
+    where
+        C: Comparator<K, Q> + Comparator<Q>,
+        Q: ?Sized,
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        self.0.truncate(end);
+        self.0.drain(..start);
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn remove_max(&mut self) -> Option<(K, V)> {
+        self.pop_last()
+    }
+
+    /// Iterates over the keys in `self` or `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn keys_union<'a, W>(&'a self, other: &'a VecMap<K, W, C>) -> KeysUnion<'a, K, V, W, C> {
+        KeysUnion(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the keys in both `self` and `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn keys_intersection<'a, W>(
+        &'a self,
+        other: &'a VecMap<K, W, C>,
+    ) -> KeysIntersection<'a, K, V, W, C> {
+        KeysIntersection(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the keys in `self` but not in `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn keys_difference<'a, W>(
+        &'a self,
+        other: &'a VecMap<K, W, C>,
+    ) -> KeysDifference<'a, K, V, W, C> {
+        KeysDifference(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the keys in exactly one of `self` and `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn keys_symmetric_difference<'a, W>(
+        &'a self,
+        other: &'a VecMap<K, W, C>,
+    ) -> KeysSymmetricDifference<'a, K, V, W, C> {
+        KeysSymmetricDifference(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the keys in both maps, with the values from each.
+    #[inline]
+    #[must_use]
+    pub fn join<'a, W>(&'a self, other: &'a VecMap<K, W, C>) -> Join<'a, K, V, W, C> {
+        Join(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the entries of `self`, with the value of the same key in
+    /// `other` if there is one.
+    #[inline]
+    #[must_use]
+    pub fn left_join<'a, W>(&'a self, other: &'a VecMap<K, W, C>) -> LeftJoin<'a, K, V, W, C> {
+        LeftJoin(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the entries of `other`, with the value of the same key in
+    /// `self` if there is one.
+    #[inline]
+    #[must_use]
+    pub fn right_join<'a, W>(&'a self, other: &'a VecMap<K, W, C>) -> RightJoin<'a, K, V, W, C> {
+        RightJoin(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the keys in either map, with the values from each map
+    /// that has the key.
+    #[inline]
+    #[must_use]
+    pub fn outer_join<'a, W>(&'a self, other: &'a VecMap<K, W, C>) -> OuterJoin<'a, K, V, W, C> {
+        OuterJoin(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the entries of `self` whose keys are in `other`.
+    #[inline]
+    #[must_use]
+    pub fn semi_join<'a, W>(&'a self, other: &'a VecMap<K, W, C>) -> SemiJoin<'a, K, V, W, C> {
+        SemiJoin(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the entries of `self` whose keys are not in `other`.
+    #[inline]
+    #[must_use]
+    pub fn anti_join<'a, W>(&'a self, other: &'a VecMap<K, W, C>) -> AntiJoin<'a, K, V, W, C> {
+        AntiJoin(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Pairs every entry of `self` with the entry of `other` selected by `direction`.
+    ///
+    /// `distance` measures how far apart two keys are. It is used to pick the
+    /// closer entry in [`AsofDirection::Nearest`] mode and to enforce
+    /// [`AsofJoin::tolerance`].
+    #[inline]
+    #[must_use]
+    pub fn asof_join<'a, W, D, F>(
+        &'a self,
+        other: &'a VecMap<K, W, C>,
+        direction: AsofDirection,
+        distance: F,
+    ) -> AsofJoin<'a, K, V, W, D, F, C>
+    where
+        D: Ord,
+        F: FnMut(&K, &K) -> D,
+    {
+        AsofJoin {
+            lhs: self.0.as_slice(),
+            rhs: other.0.as_slice(),
+            cmp: &self.1,
+            pos: 0,
+            direction,
+            distance,
+            tolerance: None,
         }
-        let guard = Guard {
-            remove_cnt,
-            v: &mut self.0,
-        };
-        unsafe {
-            let entries: *mut [(K, V)] = guard.v.get_unchecked_mut(..remove_cnt);
-            ptr::drop_in_place(entries);
+    }
+
+    /// Like [`asof_join`](Self::asof_join), for keys without a distance.
+    ///
+    /// [`AsofDirection::Nearest`] prefers the backward entry whenever both
+    /// exist, since every pair of candidates ties.
+    #[inline]
+    #[must_use]
+    pub fn asof_join_by_direction<'a, W>(
+        &'a self,
+        other: &'a VecMap<K, W, C>,
+        direction: AsofDirection,
+    ) -> AsofJoinByDirection<'a, K, V, W, C> {
+        let distance: fn(&K, &K) = |_, _| ();
+        self.asof_join(other, direction, distance)
+    }
+
+    /// Compares `self` (old) with `other` (new) in a single pass.
+    ///
+    /// Unchanged entries are skipped unless [`Diff::with_unchanged`] is called.
+    #[inline]
+    #[must_use]
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Diff<'a, K, V, C>
+    where
+        V: PartialEq,
+    {
+        Diff {
+            lhs: self.0.as_slice(),
+            rhs: other.0.as_slice(),
+            cmp: &self.1,
+            unchanged: false,
         }
-        drop(guard);
     }
 
+    /// Returns a set of the keys, ordered by the same comparator.
+    #[inline]
+    #[must_use]
+    pub fn keys_set(&self) -> VecSet<K, C>
+    where
+        K: Clone,
+        C: Clone,
+    {
+        VecSet::from_vec_unchecked(self.keys().cloned().collect(), self.1.clone())
+    }
+
+    fn retain_by_keys(&mut self, keys: &VecSet<K, C>, keep_found: bool) {
+        let mut rhs = keys.as_slice();
+        self.0.retain(|(k, _)| {
+            while let [y, ys @ ..] = rhs {
+                match self.1.compare(y, k) {
+                    Ordering::Less => rhs = ys,
+                    Ordering::Greater => break,
+                    Ordering::Equal => {
+                        rhs = ys;
+                        return keep_found;
+                    }
+                }
+            }
+            !keep_found
+        });
+    }
+
+    /// Keeps only the entries whose keys are in `keys`.
+    #[inline]
+    pub fn retain_keys(&mut self, keys: &VecSet<K, C>) {
+        self.retain_by_keys(keys, true);
+    }
+
+    /// Removes the entries whose keys are in `keys`.
+    #[inline]
+    pub fn remove_keys(&mut self, keys: &VecSet<K, C>) {
+        self.retain_by_keys(keys, false);
+    }
+
+    /// Removes the entries whose keys are in `keys` and returns them as a new map.
+    #[inline]
+    pub fn extract(&mut self, keys: &VecSet<K, C>) -> Self
+    where
+        C: Clone,
+    {
+        let mut walk = MergeWalk::<_, _, Lookup, _>::new(&self.0, keys.as_slice(), &self.1);
+        let matched = iter::from_fn(|| walk.next_both()).count();
+
+        let cmp = self.1.clone();
+        let mut rhs = keys.as_slice();
+        let mut ans = Vec::with_capacity(matched);
+        ans.extend(self.extract_if(|k, _| {
+            while let [y, ys @ ..] = rhs {
+                match cmp.compare(y, k) {
+                    Ordering::Less => rhs = ys,
+                    Ordering::Greater => break,
+                    Ordering::Equal => {
+                        rhs = ys;
+                        return true;
+                    }
+                }
+            }
+            false
+        }));
+
+        Self(ans, cmp)
+    }
+
+    /// Keeps the entries whose keys are in `other`, combining their values
+    /// with `f(key, self_value, other_value)`.
+    #[inline]
+    #[must_use]
+    pub fn intersection_with<W, U>(
+        self,
+        other: &VecMap<K, W, C>,
+        mut f: impl FnMut(&K, V, &W) -> U,
+    ) -> VecMap<K, U, C> {
+        let mut rhs = other.0.as_slice();
+        let mut ans = Vec::with_capacity(self.0.len().min(rhs.len()));
+
+        for (k, v) in self.0 {
+            loop {
+                let [y, ys @ ..] = rhs else {
+                    return VecMap(ans, self.1);
+                };
+                match self.1.compare(&y.0, &k) {
+                    Ordering::Less => rhs = ys,
+                    Ordering::Greater => break,
+                    Ordering::Equal => {
+                        rhs = ys;
+                        let u = f(&k, v, &y.1);
+                        ans.push((k, u));
+                        break;
+                    }
+                }
+            }
+        }
+
+        VecMap(ans, self.1)
+    }
+
+    /// Removes the entries whose keys are in `other`.
+    #[inline]
+    #[must_use]
+    pub fn difference<W>(mut self, other: &VecMap<K, W, C>) -> Self {
+        let mut rhs = other.0.as_slice();
+        self.0.retain(|(k, _)| {
+            while let [y, ys @ ..] = rhs {
+                match self.1.compare(&y.0, k) {
+                    Ordering::Less => rhs = ys,
+                    Ordering::Greater => break,
+                    Ordering::Equal => {
+                        rhs = ys;
+                        return false;
+                    }
+                }
+            }
+            true
+        });
+        self
+    }
+
+    /// Applies a batch of operations in a single linear merge.
+    ///
+    /// Operations on the same key are applied in the order they are given.
+    /// Returns one outcome per operation, in the order they are given.
+    ///
+    /// If the comparator panics, `self` stays sorted with only part of the
+    /// batch applied.
+    #[inline]
+    pub fn apply_batch(
+        &mut self,
+        ops: impl IntoIterator<Item = BatchOp<K, V>>,
+    ) -> Vec<BatchOutcome<V>> {
+        let mut ops: Vec<(usize, BatchOp<K, V>)> = ops.into_iter().enumerate().collect();
+        ops.sort_by(|lhs, rhs| self.1.compare(lhs.1.key(), rhs.1.key()));
+
+        let mut outcomes: Vec<BatchOutcome<V>> = Vec::with_capacity(ops.len());
+        outcomes.resize_with(ops.len(), || BatchOutcome::NotFound);
+
+        let cmp = &self.1;
+        merge_grouped(
+            &mut self.0,
+            ops,
+            |x, (_, op)| cmp.compare(&x.0, op.key()),
+            |(_, lhs), (_, rhs)| cmp.compare(lhs.key(), rhs.key()).is_eq(),
+            |mut cur, group| {
+                for (idx, op) in group {
+                    outcomes[idx] = match op {
+                        BatchOp::Insert(key, value) => match cur {
+                            Some(_) => BatchOutcome::Occupied(value),
+                            None => {
+                                cur = Some((key, value));
+                                BatchOutcome::Inserted
+                            }
+                        },
+                        BatchOp::Upsert(key, value) => match cur {
+                            Some((_, ref mut v)) => BatchOutcome::Replaced(mem::replace(v, value)),
+                            None => {
+                                cur = Some((key, value));
+                                BatchOutcome::Inserted
+                            }
+                        },
+                        BatchOp::Remove(_) => match cur.take() {
+                            Some((_, v)) => BatchOutcome::Removed(v),
+                            None => BatchOutcome::NotFound,
+                        },
+                    };
+                }
+                cur
+            },
+        );
+        outcomes
+    }
+
+    /// Applies a batch of patch operations in a single linear merge.
+    ///
+    /// Operations are applied in key order. Operations on the same key are
+    /// applied in the order they are given. Returns the rejected operations,
+    /// also in key order.
+    ///
+    /// If the comparator panics, `self` stays sorted with only part of the
+    /// batch applied.
+    #[inline]
+    pub fn apply_patch(
+        &mut self,
+        patch: impl IntoIterator<Item = PatchOp<K, V>>,
+        mode: PatchMode,
+    ) -> Vec<PatchConflict<K, V>>
+    where
+        V: PartialEq,
+    {
+        let mut ops: Vec<PatchOp<K, V>> = patch.into_iter().collect();
+        ops.sort_by(|lhs, rhs| self.1.compare(lhs.key(), rhs.key()));
+
+        let mut verdicts: Vec<Verdict> = Vec::with_capacity(ops.len());
+        {
+            let mut entries = self.0.as_slice();
+            let mut state: Option<&V> = None;
+            for (i, op) in ops.iter().enumerate() {
+                let new_group = i == 0 || self.1.compare(ops[i - 1].key(), op.key()).is_ne();
+                if new_group {
+                    while let [x, xs @ ..] = entries {
+                        if self.1.compare(&x.0, op.key()).is_ge() {
+                            break;
+                        }
+                        entries = xs;
+                    }
+                    state = match entries {
+                        [x, ..] if self.1.compare(&x.0, op.key()).is_eq() => Some(&x.1),
+                        _ => None,
+                    };
+                }
+                let verdict = match (op, state) {
+                    (PatchOp::Insert(_, v), _) => {
+                        state = Some(v);
+                        Verdict::Apply
+                    }
+                    (PatchOp::Remove(_), None) | (PatchOp::Replace { .. }, None) => {
+                        Verdict::Conflict(ConflictKind::Missing)
+                    }
+                    (PatchOp::Remove(_), Some(_)) => {
+                        state = None;
+                        Verdict::Apply
+                    }
+                    (PatchOp::Replace { expected, new, .. }, Some(cur)) => {
+                        if cur == expected {
+                            state = Some(new);
+                            Verdict::Apply
+                        } else {
+                            Verdict::Conflict(ConflictKind::Mismatch)
+                        }
+                    }
+                };
+                verdicts.push(verdict);
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        let mut accepted = Vec::with_capacity(ops.len());
+        for (op, verdict) in ops.into_iter().zip(verdicts) {
+            match verdict {
+                Verdict::Apply => accepted.push(op),
+                Verdict::Conflict(kind) => conflicts.push(kind.reject(op)),
+            }
+        }
+        if mode == PatchMode::Atomic && !conflicts.is_empty() {
+            return conflicts;
+        }
+
+        let cmp = &self.1;
+        merge_grouped(
+            &mut self.0,
+            accepted,
+            |x, op| cmp.compare(&x.0, op.key()),
+            |lhs, rhs| cmp.compare(lhs.key(), rhs.key()).is_eq(),
+            |mut cur, group| {
+                for op in group {
+                    match op {
+                        PatchOp::Insert(key, value) => match cur {
+                            Some((_, ref mut v)) => *v = value,
+                            None => cur = Some((key, value)),
+                        },
+                        PatchOp::Remove(_) => cur = None,
+                        PatchOp::Replace { new, .. } => {
+                            if let Some((_, ref mut v)) = cur {
+                                *v = new;
+                            }
+                        }
+                    }
+                }
+                cur
+            },
+        );
+        conflicts
+    }
+
+    /// Calls `f` on the value of every key in `keys` that is present in the map.
+    #[inline]
+    pub fn apply(&self, keys: &VecSet<K, C>, mut f: impl FnMut(&V)) {
+        let mut walk = MergeWalk::<_, _, Lookup, _>::new(&self.0, keys.as_slice(), &self.1);
+        while let Some(((_, v), _)) = walk.next_both() {
+            f(v);
+        }
+    }
+
+    /// Calls `f` on the value of every key in `keys` that is present in the map,
+    /// allowing it to be modified.
+    #[inline]
+    pub fn apply_mut(&mut self, keys: &VecSet<K, C>, mut f: impl FnMut(&mut V)) {
+        let mut lhs = self.0.as_mut_slice();
+        let mut rhs = keys.as_slice();
+        while let ([x, ..], [y, ys @ ..]) = (&*lhs, rhs) {
+            match self.1.compare(&x.0, y) {
+                Ordering::Less => lhs = &mut mem::take(&mut lhs)[1..],
+                Ordering::Greater => rhs = ys,
+                Ordering::Equal => {
+                    if let [(_, v), xs @ ..] = mem::take(&mut lhs) {
+                        f(v);
+                        lhs = xs;
+                    }
+                    rhs = ys;
+                }
+            }
+        }
+    }
+
+    /// Calls `f` on every entry whose key is in `keys`.
+    #[inline]
+    pub fn apply_with_key(&self, keys: &VecSet<K, C>, mut f: impl FnMut(&K, &V)) {
+        let mut walk = MergeWalk::<_, _, Lookup, _>::new(&self.0, keys.as_slice(), &self.1);
+        while let Some(((k, v), _)) = walk.next_both() {
+            f(k, v);
+        }
+    }
+
+    /// Calls `f` once for every key in `keys`, in order, reporting whether
+    /// the key is present in the map.
+    #[inline]
+    pub fn apply_outer<'a>(
+        &'a self,
+        keys: &'a VecSet<K, C>,
+        mut f: impl FnMut(KeyLookup<'a, K, V>),
+    ) {
+        let mut walk = MergeWalk::<_, _, Lookup, _>::new(&self.0, keys.as_slice(), &self.1);
+        while let Some((entry, key)) = walk.next_right() {
+            f(match entry {
+                Some((k, v)) => KeyLookup::Found(k, v),
+                None => KeyLookup::Missing(key),
+            });
+        }
+    }
+}
+
+impl<K: Ord, V> From<Vec<(K, V)>> for VecMap<K, V> {
+    #[inline]
+    fn from(v: Vec<(K, V)>) -> Self {
+        Self::from_vec(v)
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for VecMap<K, V> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+/// Entries from the iterator overwrite the values of existing keys.
+/// If the iterator yields a key more than once, the last value wins.
+/// If the comparator panics, the map stays sorted with only part of the
+/// iterator merged.
+impl<K, V, C: Comparator<K>> Extend<(K, V)> for VecMap<K, V, C> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let mut batch: Vec<(K, V)> = iter.into_iter().collect();
+        batch.reverse();
+        batch.sort_by(|lhs, rhs| self.1.compare(&lhs.0, &rhs.0));
+        batch.dedup_by(|x, first| self.1.compare(&x.0, &first.0).is_eq());
+        self.merge_sorted_vec(batch, |_, _, v| v);
+    }
+}
+
+impl<'a, K, V, C> Extend<(&'a K, &'a V)> for VecMap<K, V, C>
+where
+    K: Copy,
+    V: Copy,
+    C: Comparator<K>,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(&k, &v)| (k, v)));
+    }
+}
+
+impl<'a, K, V, C> Extend<&'a (K, V)> for VecMap<K, V, C>
+where
+    K: Copy,
+    V: Copy,
+    C: Comparator<K>,
+{
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a (K, V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<K, V, C: Default> Default for VecMap<K, V, C> {
+    #[inline]
+    fn default() -> Self {
+        Self(Vec::new(), C::default())
+    }
+}
+
+/// Compares the entries in order. The comparators are not compared.
+impl<K: PartialEq, V: PartialEq, C> PartialEq for VecMap<K, V, C> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Eq, V: Eq, C> Eq for VecMap<K, V, C> {}
+
+/// Compares the entries lexicographically, ignoring the comparators.
+impl<K: PartialOrd, V: PartialOrd, C> PartialOrd for VecMap<K, V, C> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+/// Compares the entries lexicographically, ignoring the comparators.
+impl<K: Ord, V: Ord, C> Ord for VecMap<K, V, C> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+/// Hashes the entries in order, ignoring the comparator.
+impl<K: Hash, V: Hash, C> Hash for VecMap<K, V, C> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<K, V, C> fmt::Debug for VecMap<K, V, C>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.0.iter().map(|(k, v)| (k, v));
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+pub struct Iter<'a, K, V>(slice::Iter<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = &'a (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, K, V, C> IntoIterator for &'a VecMap<K, V, C> {
+    type Item = &'a (K, V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct IterMut<'a, K, V>(slice::IterMut<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (&*k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+
+impl<'a, K, V, C> IntoIterator for &'a mut VecMap<K, V, C> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+pub struct IntoIter<K, V>(vec::IntoIter<(K, V)>);
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K: Clone, V: Clone> Clone for IntoIter<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K, V, C> IntoIterator for VecMap<K, V, C> {
+    type Item = (K, V);
+
+    type IntoIter = IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+/// An iterator over the keys of a `VecMap`, created by [`VecMap::keys`].
+pub struct Keys<'a, K, V>(slice::Iter<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+impl<K, V> Clone for Keys<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the values of a `VecMap`, created by [`VecMap::values`].
+pub struct Values<'a, K, V>(slice::Iter<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+impl<K, V> Clone for Values<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// A mutable iterator over the values of a `VecMap`, created by [`VecMap::values_mut`].
+pub struct ValuesMut<'a, K, V>(slice::IterMut<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for ValuesMut<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for ValuesMut<'_, K, V> {}
+
+/// An owning iterator over the keys of a `VecMap`, created by [`VecMap::into_keys`].
+pub struct IntoKeys<K, V>(vec::IntoIter<(K, V)>);
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoKeys<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for IntoKeys<K, V> {}
+
+impl<K: Clone, V: Clone> Clone for IntoKeys<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An owning iterator over the values of a `VecMap`, created by [`VecMap::into_values`].
+pub struct IntoValues<K, V>(vec::IntoIter<(K, V)>);
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoValues<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for IntoValues<K, V> {}
+
+impl<K: Clone, V: Clone> Clone for IntoValues<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over a range of entries, created by [`VecMap::range`] and [`VecMap::range_by`].
+pub struct Range<'a, K, V>(slice::Iter<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = &'a (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Range<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// A mutable iterator over a range of entries, created by [`VecMap::range_mut`] and
+/// [`VecMap::range_by_mut`].
+pub struct RangeMut<'a, K, V>(slice::IterMut<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for RangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (&*k, v))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for RangeMut<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<K, V> ExactSizeIterator for RangeMut<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for RangeMut<'_, K, V> {}
+
+/// A draining iterator over a range of entries, created by [`VecMap::drain_range`].
+pub struct Drain<'a, K, V>(vec::Drain<'a, (K, V)>);
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Drain<'_, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<K, V> FusedIterator for Drain<'_, K, V> {}
+
+/// An iterator that removes the entries matching a predicate, created by
+/// [`VecMap::extract_if`].
+pub struct ExtractIf<'a, K, V, F> {
+    vec: &'a mut Vec<(K, V)>,
+    idx: usize,
+    del: usize,
+    old_len: usize,
+    pred: F,
+}
+
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while self.idx < self.old_len {
+                let base = self.vec.as_mut_ptr();
+                let cur = base.add(self.idx);
+                let drained = (self.pred)(&(*cur).0, &mut (*cur).1);
+                self.idx += 1;
+                if drained {
+                    self.del += 1;
+                    return Some(ptr::read(cur));
+                }
+                if self.del > 0 {
+                    let dst = cur.sub(self.del);
+                    ptr::copy_nonoverlapping(cur, dst, 1);
+                }
+            }
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.old_len - self.idx))
+    }
+}
+
+impl<K, V, F> Drop for ExtractIf<'_, K, V, F> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            if self.idx < self.old_len && self.del > 0 {
+                let src = self.vec.as_mut_ptr().add(self.idx);
+                let dst = src.sub(self.del);
+                ptr::copy(src, dst, self.old_len - self.idx);
+            }
+            self.vec.set_len(self.old_len - self.del);
+        }
+    }
+}
+
+/// A cursor over a `VecMap`.
+///
+/// The cursor points at an entry or at the ghost position past the last
+/// entry. Moving past either end wraps through the ghost position.
+pub struct Cursor<'a, K, V> {
+    entries: &'a [(K, V)],
+    index: usize,
+}
+
+impl<K, V> Clone for Cursor<'_, K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Cursor {
+            entries: self.entries,
+            index: self.index,
+        }
+    }
+}
+
+impl<'a, K, V> Cursor<'a, K, V> {
+    /// Returns the index of the current entry, or `None` at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> Option<usize> {
+        (self.index < self.entries.len()).then_some(self.index)
+    }
+
+    /// Returns the current entry, or `None` at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn current(&self) -> Option<(&'a K, &'a V)> {
+        self.entries.get(self.index).map(|(k, v)| (k, v))
+    }
+
+    /// Returns the entry after the current one, or the first entry at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        self.entries.get(self.next_index()).map(|(k, v)| (k, v))
+    }
+
+    /// Returns the entry before the current one, or the last entry at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let prev = self.prev_index()?;
+        self.entries.get(prev).map(|(k, v)| (k, v))
+    }
+
+    fn next_index(&self) -> usize {
+        if self.index == self.entries.len() {
+            0
+        } else {
+            self.index + 1
+        }
+    }
+
+    fn prev_index(&self) -> Option<usize> {
+        self.index.checked_sub(1)
+    }
+
+    /// Moves to the next entry, or from the ghost position to the first entry.
+    #[inline]
+    pub fn move_next(&mut self) {
+        if self.index == self.entries.len() {
+            self.index = 0;
+        } else {
+            self.index += 1;
+        }
+    }
+
+    /// Moves to the previous entry, or from the first entry to the ghost position.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        if self.index == 0 {
+            self.index = self.entries.len();
+        } else {
+            self.index -= 1;
+        }
+    }
+}
+
+/// A cursor over a `VecMap` with editing operations.
+pub struct CursorMut<'a, K, V, C = Natural> {
+    entries: &'a mut Vec<(K, V)>,
+    cmp: &'a C,
+    index: usize,
+}
+
+impl<K, V, C> CursorMut<'_, K, V, C> {
+    /// Returns the index of the current entry, or `None` at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> Option<usize> {
+        (self.index < self.entries.len()).then_some(self.index)
+    }
+
+    /// Returns the current entry, or `None` at the ghost position.
+    #[inline]
+    pub fn current(&mut self) -> Option<(&K, &mut V)> {
+        self.entries.get_mut(self.index).map(|(k, v)| (&*k, v))
+    }
+
+    /// Returns the entry after the current one, or the first entry at the ghost position.
+    #[inline]
+    pub fn peek_next(&mut self) -> Option<(&K, &mut V)> {
+        let next = self.as_cursor().next_index();
+        self.entries.get_mut(next).map(|(k, v)| (&*k, v))
+    }
+
+    /// Returns the entry before the current one, or the last entry at the ghost position.
+    #[inline]
+    pub fn peek_prev(&mut self) -> Option<(&K, &mut V)> {
+        let prev = self.as_cursor().prev_index()?;
+        self.entries.get_mut(prev).map(|(k, v)| (&*k, v))
+    }
+
+    /// Moves to the next entry, or from the ghost position to the first entry.
+    #[inline]
+    pub fn move_next(&mut self) {
+        if self.index == self.entries.len() {
+            self.index = 0;
+        } else {
+            self.index += 1;
+        }
+    }
+
+    /// Moves to the previous entry, or from the first entry to the ghost position.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        if self.index == 0 {
+            self.index = self.entries.len();
+        } else {
+            self.index -= 1;
+        }
+    }
+
+    /// Returns a read-only cursor at the same position.
+    #[inline]
+    #[must_use]
+    pub fn as_cursor(&self) -> Cursor<'_, K, V> {
+        Cursor {
+            entries: self.entries,
+            index: self.index,
+        }
+    }
+
+    /// Removes the current entry and moves the cursor to the next one.
+    #[inline]
+    pub fn remove_current(&mut self) -> Option<(K, V)> {
+        (self.index < self.entries.len()).then(|| self.entries.remove(self.index))
+    }
+}
+
+impl<K, V, C: Comparator<K>> CursorMut<'_, K, V, C> {
+    /// Inserts an entry immediately before the current one.
+    ///
+    /// Fails if `key` does not sort strictly between the neighbouring keys.
+    #[inline]
+    pub fn insert_before(&mut self, key: K, value: V) -> Result<(), UnorderedKeyError> {
+        let prev = self.index.checked_sub(1).and_then(|i| self.entries.get(i));
+        if prev.is_some_and(|(k, _)| self.cmp.compare(k, &key).is_ge()) {
+            return Err(UnorderedKeyError);
+        }
+        let cur = self.entries.get(self.index);
+        if cur.is_some_and(|(k, _)| self.cmp.compare(k, &key).is_le()) {
+            return Err(UnorderedKeyError);
+        }
+        self.entries.insert(self.index, (key, value));
+        self.index += 1;
+        Ok(())
+    }
+
+    /// Inserts an entry immediately after the current one, or at the front
+    /// at the ghost position.
+    ///
+    /// Fails if `key` does not sort strictly between the neighbouring keys.
+    #[inline]
+    pub fn insert_after(&mut self, key: K, value: V) -> Result<(), UnorderedKeyError> {
+        let ghost = self.index == self.entries.len();
+        let at = if ghost { 0 } else { self.index + 1 };
+        if !ghost && self.cmp.compare(&self.entries[self.index].0, &key).is_ge() {
+            return Err(UnorderedKeyError);
+        }
+        let next = self.entries.get(at);
+        if next.is_some_and(|(k, _)| self.cmp.compare(k, &key).is_le()) {
+            return Err(UnorderedKeyError);
+        }
+        self.entries.insert(at, (key, value));
+        if ghost {
+            self.index += 1;
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over the keys in either of two maps, created by [`VecMap::keys_union`].
+pub struct KeysUnion<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for KeysUnion<'a, K, V, W, C> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|item| match item {
+            EitherOrBoth::Left(x) | EitherOrBoth::Both(x, _) => &x.0,
+            EitherOrBoth::Right(y) => &y.0,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for KeysUnion<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for KeysUnion<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the keys in both of two maps, created by [`VecMap::keys_intersection`].
+pub struct KeysIntersection<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for KeysIntersection<'a, K, V, W, C> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_both().map(|(x, _)| &x.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.0.remaining();
+        (0, Some(l.min(r)))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for KeysIntersection<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for KeysIntersection<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the keys only in the first of two maps, created by
+/// [`VecMap::keys_difference`].
+pub struct KeysDifference<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for KeysDifference<'a, K, V, W, C> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (x, None) = self.0.next_left()? {
+                return Some(&x.0);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.0.remaining();
+        (l.saturating_sub(r), Some(l))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for KeysDifference<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for KeysDifference<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the keys in exactly one of two maps, created by
+/// [`VecMap::keys_symmetric_difference`].
+pub struct KeysSymmetricDifference<'a, K, V, W, C = Natural>(
+    MergeWalk<'a, (K, V), (K, W), First, C>,
+);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for KeysSymmetricDifference<'a, K, V, W, C> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.0.next()? {
+                EitherOrBoth::Left(x) => return Some(&x.0),
+                EitherOrBoth::Right(y) => return Some(&y.0),
+                EitherOrBoth::Both(..) => {}
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.0.remaining();
+        (l.abs_diff(r), l.checked_add(r))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for KeysSymmetricDifference<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for KeysSymmetricDifference<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Whether a key passed to [`VecMap::apply_outer`] is present in the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyLookup<'a, K, V> {
+    /// The key is present, along with its value.
+    Found(&'a K, &'a V),
+    /// The key is missing.
+    Missing(&'a K),
+}
+
+/// An iterator over the keys in both of two maps with both values, created by
+/// [`VecMap::join`].
+pub struct Join<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for Join<'a, K, V, W, C> {
+    type Item = (&'a K, &'a V, &'a W);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_both().map(|(x, y)| (&x.0, &x.1, &y.1))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.0.remaining();
+        (0, Some(l.min(r)))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for Join<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for Join<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the entries of the first map with the matching values of the second,
+/// created by [`VecMap::left_join`].
+pub struct LeftJoin<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for LeftJoin<'a, K, V, W, C> {
+    type Item = (&'a K, &'a V, Option<&'a W>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = self.0.next_left()?;
+        Some((&x.0, &x.1, y.map(|y| &y.1)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, _) = self.0.remaining();
+        (l, Some(l))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> ExactSizeIterator for LeftJoin<'_, K, V, W, C> {}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for LeftJoin<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for LeftJoin<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the entries of the second map with the matching values of the first,
+/// created by [`VecMap::right_join`].
+pub struct RightJoin<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for RightJoin<'a, K, V, W, C> {
+    type Item = (&'a K, Option<&'a V>, &'a W);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x, y) = self.0.next_right()?;
+        Some((&y.0, x.map(|x| &x.1), &y.1))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, r) = self.0.remaining();
+        (r, Some(r))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> ExactSizeIterator for RightJoin<'_, K, V, W, C> {}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for RightJoin<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for RightJoin<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the keys in either of two maps with their values, created by
+/// [`VecMap::outer_join`].
+pub struct OuterJoin<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for OuterJoin<'a, K, V, W, C> {
+    type Item = (&'a K, EitherOrBoth<&'a V, &'a W>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match self.0.next()? {
+            EitherOrBoth::Left(x) => (&x.0, EitherOrBoth::Left(&x.1)),
+            EitherOrBoth::Right(y) => (&y.0, EitherOrBoth::Right(&y.1)),
+            EitherOrBoth::Both(x, y) => (&x.0, EitherOrBoth::Both(&x.1, &y.1)),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for OuterJoin<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for OuterJoin<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the entries of the first map whose keys are in the second, created by
+/// [`VecMap::semi_join`].
+pub struct SemiJoin<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for SemiJoin<'a, K, V, W, C> {
+    type Item = &'a (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_both().map(|(x, _)| x)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.0.remaining();
+        (0, Some(l.min(r)))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for SemiJoin<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for SemiJoin<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the entries of the first map whose keys are not in the second, created
+/// by [`VecMap::anti_join`].
+pub struct AntiJoin<'a, K, V, W, C = Natural>(MergeWalk<'a, (K, V), (K, W), First, C>);
+
+impl<'a, K, V, W, C: Comparator<K>> Iterator for AntiJoin<'a, K, V, W, C> {
+    type Item = &'a (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (x, None) = self.0.next_left()? {
+                return Some(x);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.0.remaining();
+        (l.saturating_sub(r), Some(l))
+    }
+}
+
+impl<K, V, W, C: Comparator<K>> FusedIterator for AntiJoin<'_, K, V, W, C> {}
+
+impl<K, V, W, C> Clone for AntiJoin<'_, K, V, W, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Which right entry an [`AsofJoin`] matches to each left entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsofDirection {
+    /// Match the right entry with the greatest key less than or equal to the left key.
+    Backward,
+    /// Match the right entry with the smallest key greater than or equal to the left key.
+    Forward,
+    /// Match whichever of the backward and forward entries is closer, preferring backward on ties.
+    Nearest,
+}
+
+/// An iterator matching each entry of the first map to a nearby entry of the second,
+/// created by [`VecMap::asof_join`].
+pub struct AsofJoin<'a, K, V, W, D, F, C = Natural> {
+    lhs: &'a [(K, V)],
+    rhs: &'a [(K, W)],
+    cmp: &'a C,
+    pos: usize,
+    direction: AsofDirection,
+    distance: F,
+    tolerance: Option<D>,
+}
+
+/// An [`AsofJoin`] without a distance, returned by [`VecMap::asof_join_by_direction`].
+pub type AsofJoinByDirection<'a, K, V, W, C = Natural> = AsofJoin<'a, K, V, W, (), fn(&K, &K), C>;
+
+impl<K, V, W, D, F, C> AsofJoin<'_, K, V, W, D, F, C> {
+    /// Rejects matches whose distance from the left key is greater than `max`.
     #[inline]
     #[must_use]
-    pub fn remove_max(&mut self) -> Option<(K, V)> {
-        self.0.pop()
+    pub fn tolerance(mut self, max: D) -> Self {
+        self.tolerance = Some(max);
+        self
     }
+}
+
+impl<'a, K, V, W, D, F, C> Iterator for AsofJoin<'a, K, V, W, D, F, C>
+where
+    C: Comparator<K>,
+    D: Ord,
+    F: FnMut(&K, &K) -> D,
+{
+    type Item = (&'a K, &'a V, Option<(&'a K, &'a W)>);
 
     #[inline]
-    pub fn apply(&self, keys: &VecSet<K>, mut f: impl FnMut(&V)) {
-        unsafe {
-            let mut p1 = self.0.as_ptr();
-            let e1 = p1.add(self.len());
-            let mut p2 = keys.as_slice().as_ptr();
-            let e2 = p2.add(keys.len());
+    fn next(&mut self) -> Option<Self::Item> {
+        let [x, xs @ ..] = self.lhs else {
+            return None;
+        };
+        self.lhs = xs;
 
-            while p1 < e1 && p2 < e2 {
-                let (k1, v) = &*p1;
-                let k2 = &*p2;
-                match Ord::cmp(k1, k2) {
-                    Ordering::Less => {
-                        p1 = p1.add(1);
-                    }
-                    Ordering::Greater => {
-                        p2 = p2.add(1);
-                    }
-                    Ordering::Equal => {
-                        f(v);
-                        p1 = p1.add(1);
-                        p2 = p2.add(1);
-                    }
-                }
+        let rhs = self.rhs;
+        while let Some(y) = rhs.get(self.pos) {
+            if self.cmp.compare(&y.0, &x.0).is_gt() {
+                break;
             }
+            self.pos += 1;
         }
-    }
-}
 
-impl<K: Ord, V> From<Vec<(K, V)>> for VecMap<K, V> {
-    #[inline]
-    fn from(v: Vec<(K, V)>) -> Self {
-        Self::from_vec(v)
+        let backward = self.pos.checked_sub(1).map(|idx| &rhs[idx]);
+        let forward = match backward {
+            Some(y) if self.cmp.compare(&y.0, &x.0).is_eq() => Some(y),
+            _ => rhs.get(self.pos),
+        };
+        let candidate = match self.direction {
+            AsofDirection::Backward => backward,
+            AsofDirection::Forward => forward,
+            AsofDirection::Nearest => match (backward, forward) {
+                (Some(b), Some(f)) => {
+                    let db = (self.distance)(&x.0, &b.0);
+                    let df = (self.distance)(&x.0, &f.0);
+                    Some(if df < db { f } else { b })
+                }
+                (b, f) => b.or(f),
+            },
+        };
+        let matched = match (candidate, &self.tolerance) {
+            (Some(y), Some(max)) if (self.distance)(&x.0, &y.0) > *max => None,
+            (y, _) => y.map(|(k, w)| (k, w)),
+        };
+
+        Some((&x.0, &x.1, matched))
     }
-}
 
-impl<K: Ord, V> FromIterator<(K, V)> for VecMap<K, V> {
     #[inline]
-    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        Self::from_vec(iter.into_iter().collect())
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.lhs.len(), Some(self.lhs.len()))
     }
 }
 
-impl<K, V> Default for VecMap<K, V> {
-    #[inline]
-    fn default() -> Self {
-        Self::new()
-    }
+impl<K, V, W, D, F, C> ExactSizeIterator for AsofJoin<'_, K, V, W, D, F, C>
+where
+    C: Comparator<K>,
+    D: Ord,
+    F: FnMut(&K, &K) -> D,
+{
 }
 
-impl<K, V> fmt::Debug for VecMap<K, V>
+impl<K, V, W, D, F, C> FusedIterator for AsofJoin<'_, K, V, W, D, F, C>
 where
-    K: fmt::Debug,
-    V: fmt::Debug,
+    C: Comparator<K>,
+    D: Ord,
+    F: FnMut(&K, &K) -> D,
 {
+}
+
+/// A difference between two maps, yielded by [`Diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffItem<'a, K, V> {
+    /// The entry is only present in the new map.
+    Added(&'a K, &'a V),
+    /// The entry is only present in the old map.
+    Removed(&'a K, &'a V),
+    /// The key is present in both maps with different values, old first.
+    Changed(&'a K, &'a V, &'a V),
+    /// The key is present in both maps with equal values.
+    Unchanged(&'a K, &'a V),
+}
+
+/// An iterator over the differences between two maps, created by [`VecMap::diff`].
+pub struct Diff<'a, K, V, C = Natural> {
+    lhs: &'a [(K, V)],
+    rhs: &'a [(K, V)],
+    cmp: &'a C,
+    unchanged: bool,
+}
+
+impl<K, V, C> Diff<'_, K, V, C> {
+    /// Also yields [`DiffItem::Unchanged`] for entries that are equal in both maps.
     #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let entries = self.0.iter().map(|(k, v)| (k, v));
-        f.debug_map().entries(entries).finish()
+    #[must_use]
+    pub fn with_unchanged(mut self) -> Self {
+        self.unchanged = true;
+        self
     }
 }
 
-pub struct Iter<'a, K, V>(slice::Iter<'a, (K, V)>);
-
-impl<'a, K, V> Iterator for Iter<'a, K, V> {
-    type Item = &'a (K, V);
+impl<'a, K, V: PartialEq, C: Comparator<K>> Iterator for Diff<'a, K, V, C> {
+    type Item = DiffItem<'a, K, V>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        loop {
+            match (self.lhs, self.rhs) {
+                ([x, xs @ ..], [y, ys @ ..]) => match self.cmp.compare(&x.0, &y.0) {
+                    Ordering::Less => {
+                        self.lhs = xs;
+                        return Some(DiffItem::Removed(&x.0, &x.1));
+                    }
+                    Ordering::Greater => {
+                        self.rhs = ys;
+                        return Some(DiffItem::Added(&y.0, &y.1));
+                    }
+                    Ordering::Equal => {
+                        self.lhs = xs;
+                        self.rhs = ys;
+                        if x.1 != y.1 {
+                            return Some(DiffItem::Changed(&x.0, &x.1, &y.1));
+                        }
+                        if self.unchanged {
+                            return Some(DiffItem::Unchanged(&x.0, &x.1));
+                        }
+                    }
+                },
+                ([x, xs @ ..], []) => {
+                    self.lhs = xs;
+                    return Some(DiffItem::Removed(&x.0, &x.1));
+                }
+                ([], [y, ys @ ..]) => {
+                    self.rhs = ys;
+                    return Some(DiffItem::Added(&y.0, &y.1));
+                }
+                ([], []) => return None,
+            }
+        }
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        let (l, r) = (self.lhs.len(), self.rhs.len());
+        (l.abs_diff(r), l.checked_add(r))
     }
 }
-impl<'a, K, V> IntoIterator for &'a VecMap<K, V> {
-    type Item = &'a (K, V);
 
-    type IntoIter = Iter<'a, K, V>;
+impl<K, V: PartialEq, C: Comparator<K>> FusedIterator for Diff<'_, K, V, C> {}
 
+impl<K, V, C> Clone for Diff<'_, K, V, C> {
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    fn clone(&self) -> Self {
+        Self {
+            lhs: self.lhs,
+            rhs: self.rhs,
+            cmp: self.cmp,
+            unchanged: self.unchanged,
+        }
     }
 }
 
-pub struct IterMut<'a, K, V>(slice::IterMut<'a, (K, V)>);
-
-impl<'a, K, V> Iterator for IterMut<'a, K, V> {
-    type Item = &'a mut (K, V);
-
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
-    }
+/// An operation applied by [`VecMap::apply_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp<K, V> {
+    /// Inserts the entry if the key is missing.
+    Insert(K, V),
+    /// Inserts the entry, replacing the value if the key is present.
+    Upsert(K, V),
+    /// Removes the key.
+    Remove(K),
+}
 
+impl<K, V> BatchOp<K, V> {
+    /// Returns the key the operation applies to.
     #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+    #[must_use]
+    pub fn key(&self) -> &K {
+        match self {
+            BatchOp::Insert(key, _) | BatchOp::Upsert(key, _) | BatchOp::Remove(key) => key,
+        }
     }
 }
 
-impl<'a, K, V> IntoIterator for &'a mut VecMap<K, V> {
-    type Item = &'a mut (K, V);
+/// The result of a [`BatchOp`], returned by [`VecMap::apply_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOutcome<V> {
+    /// The key was missing and the entry has been inserted.
+    Inserted,
+    /// The key was present, so the value of an `Insert` is handed back.
+    Occupied(V),
+    /// The value of an `Upsert` has replaced this previous value.
+    Replaced(V),
+    /// The key has been removed along with this value.
+    Removed(V),
+    /// The key of a `Remove` was missing.
+    NotFound,
+}
 
-    type IntoIter = IterMut<'a, K, V>;
+/// An operation applied by [`VecMap::apply_patch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchOp<K, V> {
+    /// Sets the value of `key`, inserting it if it is missing.
+    Insert(K, V),
+    /// Removes `key`, which must be present.
+    Remove(K),
+    /// Replaces the value of `key`, which must currently be `expected`.
+    Replace { key: K, expected: V, new: V },
+}
 
+impl<K, V> PatchOp<K, V> {
+    /// Returns the key the operation applies to.
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
+    #[must_use]
+    pub fn key(&self) -> &K {
+        match self {
+            PatchOp::Insert(key, _) | PatchOp::Remove(key) | PatchOp::Replace { key, .. } => key,
+        }
     }
 }
 
-pub struct IntoIter<K, V>(vec::IntoIter<(K, V)>);
+/// How [`VecMap::apply_patch`] handles conflicting operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchMode {
+    /// Applies every operation that does not conflict.
+    BestEffort,
+    /// Leaves the map unchanged if any operation conflicts.
+    Atomic,
+}
 
-impl<K, V> IntoIterator for VecMap<K, V> {
-    type Item = (K, V);
+/// An operation rejected by [`VecMap::apply_patch`], along with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchConflict<K, V> {
+    /// The operation removes or replaces a key that is not present.
+    Missing(PatchOp<K, V>),
+    /// The operation expects a value that does not match the current one.
+    Mismatch(PatchOp<K, V>),
+}
 
-    type IntoIter = IntoIter<K, V>;
+/// The outcome of checking a patch operation against the state before it.
+#[derive(Clone, Copy)]
+enum Verdict {
+    Apply,
+    Conflict(ConflictKind),
+}
 
-    #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        IntoIter(self.0.into_iter())
+#[derive(Clone, Copy)]
+enum ConflictKind {
+    Missing,
+    Mismatch,
+}
+
+impl ConflictKind {
+    fn reject<K, V>(self, op: PatchOp<K, V>) -> PatchConflict<K, V> {
+        match self {
+            ConflictKind::Missing => PatchConflict::Missing(op),
+            ConflictKind::Mismatch => PatchConflict::Mismatch(op),
+        }
     }
 }
 
-impl<K, V> Iterator for IntoIter<K, V> {
-    type Item = (K, V);
+/// An element of a merge of two sorted sequences, from either side or both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EitherOrBoth<L, R> {
+    /// Only the left side has an element.
+    Left(L),
+    /// Only the right side has an element.
+    Right(R),
+    /// Both sides have an element.
+    Both(L, R),
+}
 
+impl<L, R> EitherOrBoth<L, R> {
+    /// Returns the left element, if there is one.
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+    #[must_use]
+    pub fn left(self) -> Option<L> {
+        match self {
+            EitherOrBoth::Left(l) | EitherOrBoth::Both(l, _) => Some(l),
+            EitherOrBoth::Right(_) => None,
+        }
     }
 
+    /// Returns the right element, if there is one.
     #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+    #[must_use]
+    pub fn right(self) -> Option<R> {
+        match self {
+            EitherOrBoth::Right(r) | EitherOrBoth::Both(_, r) => Some(r),
+            EitherOrBoth::Left(_) => None,
+        }
     }
 }
 
 #[must_use]
-pub enum Entry<'a, K, V>
+pub enum Entry<'a, K, V, C = Natural>
 where
     K: 'a,
     V: 'a,
 {
-    Vacant(VacantEntry<'a, K, V>),
-    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V, C>),
+    Occupied(OccupiedEntry<'a, K, V, C>),
 }
 
 #[must_use]
-pub struct VacantEntry<'a, K, V> {
-    map: &'a mut VecMap<K, V>,
+pub struct VacantEntry<'a, K, V, C = Natural> {
+    map: &'a mut VecMap<K, V, C>,
     idx: usize,
     key: K,
 }
 
 #[must_use]
-pub struct OccupiedEntry<'a, K, V> {
-    map: &'a mut VecMap<K, V>,
+pub struct OccupiedEntry<'a, K, V, C = Natural> {
+    map: &'a mut VecMap<K, V, C>,
     idx: usize,
 }
 
-impl<'a, K, V> Entry<'a, K, V> {
+impl<'a, K, V, C> Entry<'a, K, V, C> {
     #[inline]
     pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
         if let Entry::Occupied(ref mut e) = self {
@@ -480,7 +2975,7 @@ impl<'a, K, V> Entry<'a, K, V> {
     }
 }
 
-impl<'a, K, V> VacantEntry<'a, K, V> {
+impl<'a, K, V, C> VacantEntry<'a, K, V, C> {
     #[inline]
     #[must_use]
     pub fn key(&self) -> &K {
@@ -501,7 +2996,7 @@ impl<'a, K, V> VacantEntry<'a, K, V> {
     }
 }
 
-impl<'a, K, V> OccupiedEntry<'a, K, V> {
+impl<'a, K, V, C> OccupiedEntry<'a, K, V, C> {
     #[inline]
     #[must_use]
     pub fn get(&self) -> &V {
@@ -554,21 +3049,23 @@ mod serde_impl {
 
     use serde::{Deserialize, Serialize};
 
-    impl<'de, K, V> Deserialize<'de> for VecMap<K, V>
+    impl<'de, K, V, C> Deserialize<'de> for VecMap<K, V, C>
     where
-        K: Ord + Deserialize<'de>,
+        K: Deserialize<'de>,
         V: Deserialize<'de>,
+        C: Comparator<K> + Default,
     {
         #[inline]
-        fn deserialize<D>(deserializer: D) -> Result<VecMap<K, V>, D::Error>
+        fn deserialize<D>(deserializer: D) -> Result<VecMap<K, V, C>, D::Error>
         where
             D: ::serde::de::Deserializer<'de>,
         {
-            <Vec<(K, V)>>::deserialize(deserializer).map(VecMap::from_vec)
+            let entries = <Vec<(K, V)>>::deserialize(deserializer)?;
+            Ok(VecMap::from_vec_with_comparator(entries, C::default()))
         }
     }
 
-    impl<K, V> Serialize for VecMap<K, V>
+    impl<K, V, C> Serialize for VecMap<K, V, C>
     where
         K: Serialize,
         V: Serialize,
@@ -587,6 +3084,7 @@ mod serde_impl {
 mod tests {
     use super::*;
 
+    use alloc::format;
     use alloc::string::String;
     use alloc::string::ToString;
 
@@ -612,6 +3110,303 @@ mod tests {
         assert_eq!(*m1.get(&5).unwrap(), 6);
     }
 
+    #[test]
+    fn extend() {
+        let mut m: VecMap<u8, String> = VecMap::from_iter([(1, "a".into()), (4, "d".into())]);
+        m.extend([
+            (5, "e".into()),
+            (4, "x".into()),
+            (2, "b".into()),
+            (5, "y".into()),
+            (0, "z".into()),
+        ]);
+        assert!(m.keys().eq(&[0, 1, 2, 4, 5]));
+        assert_eq!(m.get(&0).unwrap(), "z");
+        assert_eq!(m.get(&1).unwrap(), "a");
+        assert_eq!(m.get(&2).unwrap(), "b");
+        assert_eq!(m.get(&4).unwrap(), "x");
+        assert_eq!(m.get(&5).unwrap(), "y");
+
+        let mut m: VecMap<u8, u8> = VecMap::new();
+        m.extend(&VecMap::from_iter([(2, 2), (1, 1)]));
+        m.extend([(&3, &3), (&1, &10)]);
+        assert!(m.iter().eq(&[(1, 10), (2, 2), (3, 3)]));
+    }
+
+    #[test]
+    fn merge_with() {
+        let mut m1: VecMap<String, Vec<u8>> =
+            VecMap::from_iter([("a".into(), vec![1]), ("c".into(), vec![3])]);
+        let m2: VecMap<String, Vec<u8>> =
+            VecMap::from_iter([("b".into(), vec![2]), ("c".into(), vec![4])]);
+
+        m1.merge_cloned_with(&m2, |_, mut v1, v2| {
+            v1.extend(v2);
+            v1
+        });
+        assert!(m1.keys().eq(["a", "b", "c"]));
+        assert_eq!(m1.get("c").unwrap(), &[3, 4]);
+
+        m1.merge_with(m2, |k, v1, v2| {
+            assert_ne!(k, "a");
+            v2.into_iter().chain(v1).collect()
+        });
+        assert_eq!(m1.get("a").unwrap(), &[1]);
+        assert_eq!(m1.get("b").unwrap(), &[2, 2]);
+        assert_eq!(m1.get("c").unwrap(), &[4, 3, 4]);
+    }
+
+    #[test]
+    fn merge_with_panic() {
+        extern crate std;
+
+        use alloc::rc::Rc;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let token = Rc::new(());
+        let mut m1: VecMap<u8, Rc<()>> = (0..4).map(|k| (k, token.clone())).collect();
+        let m2: VecMap<u8, Rc<()>> = (2..6).map(|k| (k, token.clone())).collect();
+        assert_eq!(Rc::strong_count(&token), 9);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m1.merge_with(m2, |&k, v1, _| if k == 3 { panic!() } else { v1 })
+        }));
+        assert!(result.is_err());
+        assert!(m1.keys().eq(&[0, 1, 2, 4, 5]));
+        assert_eq!(Rc::strong_count(&token), 6);
+    }
+
+    #[test]
+    fn retain() {
+        let mut m: VecMap<u8, u8> = (1..=6).map(|k| (k, k * 10)).collect();
+        m.retain(|&k, _| k % 2 == 0);
+        assert!(m.iter().eq(&[(2, 20), (4, 40), (6, 60)]));
+        m.retain_mut(|&k, v| {
+            *v += 1;
+            k != 4
+        });
+        assert!(m.iter().eq(&[(2, 21), (6, 61)]));
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut m: VecMap<u8, String> = (1..=6).map(|k| (k, k.to_string())).collect();
+        let odd: Vec<_> = m.extract_if(|&k, _| k % 2 == 1).collect();
+        assert_eq!(odd, [(1, "1".into()), (3, "3".into()), (5, "5".into())]);
+        assert!(m.keys().eq(&[2, 4, 6]));
+
+        let mut m: VecMap<u8, String> = (1..=6).map(|k| (k, k.to_string())).collect();
+        let mut iter = m.extract_if(|&k, v| {
+            v.push('!');
+            k >= 2
+        });
+        assert_eq!(iter.next(), Some((2, "2!".into())));
+        drop(iter);
+        assert!(m.iter().eq(&[
+            (1, "1!".into()),
+            (3, "3".into()),
+            (4, "4".into()),
+            (5, "5".into()),
+            (6, "6".into())
+        ]));
+    }
+
+    #[test]
+    fn extract_if_panic() {
+        extern crate std;
+
+        use alloc::rc::Rc;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let token = Rc::new(());
+        let mut m: VecMap<u8, Rc<()>> = (0..6).map(|k| (k, token.clone())).collect();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            m.extract_if(|&k, _| if k == 3 { panic!() } else { k % 2 == 0 })
+                .for_each(drop)
+        }));
+        assert!(result.is_err());
+        assert!(m.keys().eq(&[1, 3, 4, 5]));
+        drop(m);
+        assert_eq!(Rc::strong_count(&token), 1);
+    }
+
+    #[test]
+    fn map_values() {
+        let m: VecMap<u8, u32> = VecMap::from_iter([(1, 10), (2, 20), (3, 30)]);
+        let m = m.map_values(|&k, v| v + u32::from(k));
+        assert!(m.iter().eq(&[(1, 11), (2, 22), (3, 33)]));
+        let m = m.filter_map_values(|&k, v| (k != 2).then(|| v.to_string()));
+        assert!(m.iter().eq(&[(1, "11".into()), (3, "33".into())]));
+    }
+
+    #[test]
+    fn cursor() {
+        let m: VecMap<u8, u8> = VecMap::from_iter([(1, 10), (3, 30), (5, 50)]);
+        let mut c = m.lower_bound_cursor(Bound::Included(&2));
+        assert_eq!(c.current(), Some((&3, &30)));
+        assert_eq!(c.peek_prev(), Some((&1, &10)));
+        assert_eq!(c.peek_next(), Some((&5, &50)));
+        c.move_next();
+        c.move_next();
+        assert_eq!(c.index(), None);
+        assert_eq!(c.peek_next(), Some((&1, &10)));
+        assert_eq!(c.peek_prev(), Some((&5, &50)));
+        c.move_next();
+        assert_eq!(c.index(), Some(0));
+        c.move_prev();
+        assert_eq!(c.current(), None);
+
+        assert_eq!(m.lower_bound_cursor(Bound::Excluded(&3)).index(), Some(2));
+        assert_eq!(m.upper_bound_cursor(Bound::Excluded(&3)).index(), Some(0));
+        assert_eq!(m.upper_bound_cursor(Bound::Included(&3)).index(), Some(1));
+        assert_eq!(m.upper_bound_cursor(Bound::Excluded(&1)).index(), None);
+        assert_eq!(
+            m.upper_bound_cursor::<u8>(Bound::Unbounded).index(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut m: VecMap<u8, u8> = VecMap::from_iter([(2, 20), (4, 40), (6, 60)]);
+        let mut c = m.lower_bound_cursor_mut(Bound::Included(&4));
+        assert_eq!(c.insert_before(2, 0), Err(UnorderedKeyError));
+        assert_eq!(c.insert_before(4, 0), Err(UnorderedKeyError));
+        assert_eq!(c.insert_before(3, 30), Ok(()));
+        assert_eq!(c.insert_after(6, 0), Err(UnorderedKeyError));
+        assert_eq!(c.insert_after(5, 50), Ok(()));
+        *c.current().unwrap().1 += 1;
+        assert_eq!(c.as_cursor().index(), Some(2));
+        assert_eq!(c.remove_current(), Some((4, 41)));
+        assert_eq!(c.current(), Some((&5, &mut 50)));
+        c.move_next();
+        c.move_next();
+        assert_eq!(c.remove_current(), None);
+        assert_eq!(c.insert_before(7, 70), Ok(()));
+        assert_eq!(c.insert_after(1, 10), Ok(()));
+        assert_eq!(c.index(), None);
+        assert!(m.keys().eq(&[1, 2, 3, 5, 6, 7]));
+    }
+
+    #[test]
+    fn positional() {
+        let mut m: VecMap<u8, u8> = (1..=5).map(|k| (k * 2, k)).collect();
+        assert_eq!(m.get_index(1), Some((&4, &2)));
+        assert_eq!(m.get_index(5), None);
+        *m.get_index_mut(0).unwrap().1 = 0;
+        assert_eq!(m.index_of(&6), Some(2));
+        assert_eq!(m.index_of(&7), None);
+        assert_eq!(m.rank(&7), 3);
+        assert_eq!(m.rank(&6), 2);
+        assert_eq!(m.median(), Some((&6, &3)));
+        assert_eq!(m.quantile(0.0), Some((&2, &0)));
+        assert_eq!(m.quantile(0.7), Some((&6, &3)));
+        assert_eq!(m.quantile(1.0), Some((&10, &5)));
+        assert_eq!(m.remove_index(1), Some((4, 2)));
+        assert_eq!(m.remove_index(4), None);
+        assert!(m.keys().eq(&[2, 6, 8, 10]));
+        assert_eq!(m.median(), Some((&6, &3)));
+        assert_eq!(VecMap::<u8, u8>::new().median(), None);
+    }
+
+    #[test]
+    fn quantile_nan() {
+        let m: VecMap<u8, u8> = VecMap::from_iter([(1, 1), (2, 2)]);
+        assert_eq!(m.quantile(f64::NAN), None);
+        assert_eq!(m.quantile(-0.5), None);
+        assert_eq!(m.quantile(1.5), None);
+    }
+
+    #[test]
+    fn custom_comparator() {
+        struct CaseInsensitive;
+
+        impl Comparator<String, str> for CaseInsensitive {
+            fn compare(&self, key: &String, query: &str) -> Ordering {
+                let lhs = key.bytes().map(|b| b.to_ascii_lowercase());
+                lhs.cmp(query.bytes().map(|b| b.to_ascii_lowercase()))
+            }
+        }
+
+        impl Comparator<String> for CaseInsensitive {
+            fn compare(&self, key: &String, query: &String) -> Ordering {
+                Comparator::<String, str>::compare(self, key, query)
+            }
+        }
+
+        impl Comparator<str> for CaseInsensitive {
+            fn compare(&self, key: &str, query: &str) -> Ordering {
+                Comparator::<String, str>::compare(self, &key.into(), query)
+            }
+        }
+
+        let entries = ["b", "A", "c", "B"].map(|k| (k.to_string(), k.to_string()));
+        let mut m = VecMap::from_vec_with_comparator(entries.into(), CaseInsensitive);
+        assert_eq!(m.len(), 3);
+        assert!(m.keys().eq(["A", "b", "c"]));
+        assert_eq!(m.get("C").map(String::as_str), Some("c"));
+        assert!(m
+            .range::<str, _>((Bound::Excluded("a"), Bound::Included("C")))
+            .map(|(k, _)| k)
+            .eq(["b", "c"]));
+
+        *m.entry("a".into()).or_default() += "!";
+        assert_eq!(m.insert("D".into(), "D".into()), None);
+        assert!(m.values().eq(["A!", "b", "c", "D"]));
+
+        let reversed = |lhs: &u8, rhs: &u8| rhs.cmp(lhs);
+        let mut m = VecMap::with_comparator(reversed);
+        m.extend([(1, 'a'), (3, 'c'), (2, 'b')]);
+        assert!(m.keys().eq(&[3, 2, 1]));
+        assert_eq!(m.floor(&2), Some((&2, &'b')));
+        assert_eq!(m.predecessor(&2), Some((&3, &'c')));
+        m.merge_with(
+            VecMap::from_vec_with_comparator(vec![(4, 'd'), (2, 'x')], reversed),
+            |_, v, _| v,
+        );
+        assert!(m.iter().eq(&[(4, 'd'), (3, 'c'), (2, 'b'), (1, 'a')]));
+        assert!(m.keys_set().as_slice() == [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn comparable_lookup() {
+        use crate::Comparable;
+
+        struct Pair<'a>(&'a str, &'a str);
+
+        impl Comparable<(String, String)> for Pair<'_> {
+            fn compare(&self, key: &(String, String)) -> Ordering {
+                (self.0, self.1).cmp(&(key.0.as_str(), key.1.as_str()))
+            }
+        }
+
+        let key = |a: &str, b: &str| (a.to_string(), b.to_string());
+        let mut m = VecMap::from_iter([(key("a", "z"), 1), (key("b", "x"), 2), (key("b", "y"), 3)]);
+        assert_eq!(m.get(&Pair("b", "x")), Some(&2));
+        assert_eq!(m.get(&Pair("b", "z")), None);
+        assert!(m.contains_key(&Pair("a", "z")));
+        assert_eq!(m.ceiling(&Pair("b", "")), Some((&key("b", "x"), &2)));
+        assert_eq!(m.remove(&Pair("b", "y")), Some(3));
+        assert_eq!(m.rank(&Pair("c", "")), 2);
+    }
+
+    #[test]
+    fn search_by() {
+        let mut m: VecMap<(u32, u8), char> =
+            VecMap::from_iter([((1, 0), 'a'), ((2, 1), 'b'), ((2, 7), 'c'), ((4, 2), 'd')]);
+        let at = |ts: u32| move |k: &(u32, u8)| k.0.cmp(&ts);
+        assert_eq!(m.binary_search_by(at(4)), Ok(3));
+        assert_eq!(m.binary_search_by(at(3)), Err(3));
+        assert_eq!(m.get_by(at(1)), Some((&(1, 0), &'a')));
+        assert_eq!(m.get_by(at(0)), None);
+        *m.get_by_mut(at(4)).unwrap().1 = 'x';
+        assert_eq!(m.partition_point(|k| k.0 < 3), 3);
+        assert!(m.range_by(at(2)).map(|(_, v)| *v).eq(['b', 'c']));
+        assert_eq!(m.range_by(at(3)).count(), 0);
+        m.range_by_mut(at(2)).for_each(|(_, v)| *v = '-');
+        assert!(m.values().eq(&['a', '-', '-', 'x']));
+    }
+
     #[test]
     fn remove_less_than() {
         let mut m: VecMap<u8, String> = VecMap::from_vec(vec![
@@ -627,6 +3422,458 @@ mod tests {
         assert!(m.get(&4).is_none());
         assert!(m.get(&5).is_some());
         assert!(m.get(&7).is_some());
+
+        m.remove_less_than(&8);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn remove_range() {
+        let new = || -> VecMap<u8, String> { (1..=6).map(|k| (k, k.to_string())).collect() };
+        let keys = |m: &VecMap<u8, String>| m.iter().map(|&(k, _)| k).collect::<Vec<_>>();
+
+        let mut m = new();
+        m.remove_range(3..5);
+        assert_eq!(keys(&m), [1, 2, 5, 6]);
+        m.remove_range(5..);
+        assert_eq!(keys(&m), [1, 2]);
+        m.remove_range(..);
+        assert!(m.is_empty());
+
+        let mut m = new();
+        let drained: Vec<_> = m.drain_range(2..=4).collect();
+        assert_eq!(drained, [(2, "2".into()), (3, "3".into()), (4, "4".into())]);
+        assert_eq!(keys(&m), [1, 5, 6]);
+
+        let mut m = new();
+        let mut drain = m.drain_range(..);
+        assert_eq!(drain.next_back(), Some((6, "6".into())));
+        assert_eq!(drain.next(), Some((1, "1".into())));
+        drop(drain);
+        assert!(m.is_empty());
+
+        let mut m = new();
+        m.retain_range(2..5);
+        assert_eq!(keys(&m), [2, 3, 4]);
+        m.retain_range(4..);
+        assert_eq!(keys(&m), [4]);
+        m.retain_range(..0);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn range() {
+        let mut m: VecMap<u8, u8> = VecMap::from_iter([(1, 1), (3, 3), (5, 5), (7, 7)]);
+
+        let keys = |r: Range<'_, u8, u8>| r.map(|&(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys(m.range(..)), [1, 3, 5, 7]);
+        assert_eq!(keys(m.range(3..)), [3, 5, 7]);
+        assert_eq!(keys(m.range(2..7)), [3, 5]);
+        assert_eq!(keys(m.range(2..=7)), [3, 5, 7]);
+        assert_eq!(
+            keys(m.range((Bound::Excluded(3), Bound::Unbounded))),
+            [5, 7]
+        );
+        assert_eq!(keys(m.range(8..)), []);
+        assert_eq!(keys(m.range(4..4)), []);
+        assert!(m.range(..=5).rev().map(|&(k, _)| k).eq([5, 3, 1]));
+
+        for (_, v) in m.range_mut(3..=5) {
+            *v *= 10;
+        }
+        assert_eq!(m.get(&1), Some(&1));
+        assert_eq!(m.get(&3), Some(&30));
+        assert_eq!(m.get(&5), Some(&50));
+        assert_eq!(m.get(&7), Some(&7));
+    }
+
+    #[test]
+    #[should_panic = "range start is greater than range end in VecMap"]
+    fn range_panic() {
+        let m: VecMap<u8, u8> = VecMap::from_iter([(1, 1), (3, 3)]);
+        let _ = m.range((Bound::Included(3), Bound::Excluded(1)));
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut m: VecMap<u8, u8> = VecMap::from_iter([(1, 1), (2, 2), (3, 3)]);
+        for (&k, v) in &mut m {
+            *v += k;
+        }
+        for v in m.values_mut() {
+            *v *= 10;
+        }
+        assert!(m.iter().eq(&[(1, 20), (2, 40), (3, 60)]));
+    }
+
+    #[test]
+    fn iterators() {
+        let m: VecMap<u8, char> = VecMap::from_iter([(3, 'c'), (1, 'a'), (2, 'b')]);
+
+        assert_eq!(m.iter().len(), 3);
+        assert!(m.iter().rev().eq(&[(3, 'c'), (2, 'b'), (1, 'a')]));
+        assert!(m.keys().eq(&[1, 2, 3]));
+        assert!(m.keys().rev().eq(&[3, 2, 1]));
+        assert!(m.values().eq(&['a', 'b', 'c']));
+
+        let mut keys = m.keys();
+        assert_eq!(keys.next(), Some(&1));
+        let rest = keys.clone();
+        assert_eq!(keys.len(), 2);
+        assert!(rest.eq(keys));
+
+        let mut iter = m.clone().into_iter();
+        assert_eq!(iter.next_back(), Some((3, 'c')));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next(), Some((1, 'a')));
+        assert_eq!(iter.next(), Some((2, 'b')));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        assert!(m.clone().into_keys().rev().eq([3, 2, 1]));
+        assert!(m.into_values().eq(['a', 'b', 'c']));
+    }
+
+    #[test]
+    fn navigation() {
+        let mut m: VecMap<u8, u8> = VecMap::from_iter([(2, 20), (4, 40), (6, 60)]);
+
+        assert_eq!(m.first_key_value(), Some((&2, &20)));
+        assert_eq!(m.last_key_value(), Some((&6, &60)));
+
+        assert_eq!(m.floor(&1), None);
+        assert_eq!(m.floor(&4), Some((&4, &40)));
+        assert_eq!(m.floor(&5), Some((&4, &40)));
+        assert_eq!(m.floor(&9), Some((&6, &60)));
+
+        assert_eq!(m.ceiling(&1), Some((&2, &20)));
+        assert_eq!(m.ceiling(&4), Some((&4, &40)));
+        assert_eq!(m.ceiling(&5), Some((&6, &60)));
+        assert_eq!(m.ceiling(&7), None);
+
+        assert_eq!(m.predecessor(&2), None);
+        assert_eq!(m.predecessor(&4), Some((&2, &20)));
+        assert_eq!(m.predecessor(&5), Some((&4, &40)));
+
+        assert_eq!(m.successor(&6), None);
+        assert_eq!(m.successor(&4), Some((&6, &60)));
+        assert_eq!(m.successor(&0), Some((&2, &20)));
+
+        assert_eq!(m.lower_bound(&4), (1, Some((&4, &40))));
+        assert_eq!(m.lower_bound(&5), (2, Some((&6, &60))));
+        assert_eq!(m.upper_bound(&4), (2, Some((&6, &60))));
+        assert_eq!(m.upper_bound(&6), (3, None));
+
+        *m.floor_mut(&3).unwrap().1 += 1;
+        *m.ceiling_mut(&3).unwrap().1 += 1;
+        *m.predecessor_mut(&7).unwrap().1 += 1;
+        assert!(m.successor_mut(&7).is_none());
+        assert_eq!(m.get(&2), Some(&21));
+        assert_eq!(m.get(&4), Some(&41));
+        assert_eq!(m.get(&6), Some(&61));
+
+        *m.first_entry().unwrap().get_mut() += 1;
+        assert_eq!(m.last_entry().unwrap().remove_entry(), (6, 61));
+        assert_eq!(m.pop_first(), Some((2, 22)));
+        assert_eq!(m.pop_last(), Some((4, 41)));
+        assert_eq!(m.pop_first(), None);
+        assert!(m.first_entry().is_none());
+        assert!(m.last_entry().is_none());
+    }
+
+    #[test]
+    fn keys_set_ops() {
+        let m1: VecMap<u8, char> = VecMap::from_iter([(1, 'a'), (2, 'b'), (3, 'c'), (5, 'e')]);
+        let m2: VecMap<u8, u32> = VecMap::from_iter([(2, 20), (4, 40), (5, 50), (6, 60)]);
+
+        assert!(m1.keys_union(&m2).eq(&[1, 2, 3, 4, 5, 6]));
+        assert!(m1.keys_intersection(&m2).eq(&[2, 5]));
+        assert!(m1.keys_difference(&m2).eq(&[1, 3]));
+        assert!(m2.keys_difference(&m1).eq(&[4, 6]));
+        assert!(m1.keys_symmetric_difference(&m2).eq(&[1, 3, 4, 6]));
+        assert_eq!(m1.keys_intersection(&m2).count(), 2);
+
+        let m3: VecMap<u8, u32> = VecMap::from_iter([(2, 20)]);
+        assert_eq!(m1.keys_symmetric_difference(&m3).size_hint(), (3, Some(5)));
+    }
+
+    #[test]
+    fn ordering() {
+        let a: VecMap<u8, u8> = VecMap::from_iter([(1, 1), (2, 2)]);
+        let b: VecMap<u8, u8> = VecMap::from_iter([(1, 1), (2, 3)]);
+        let c: VecMap<u8, u8> = VecMap::from_iter([(1, 1), (3, 0)]);
+        assert!(a < b);
+        assert!(b < c);
+        assert!(VecMap::new() < a);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn joins() {
+        let m1: VecMap<u8, char> = VecMap::from_iter([(1, 'a'), (2, 'b'), (4, 'd'), (5, 'e')]);
+        let m2: VecMap<u8, u32> = VecMap::from_iter([(2, 20), (3, 30), (5, 50), (6, 60)]);
+
+        assert!(m1.join(&m2).eq([(&2, &'b', &20), (&5, &'e', &50)]));
+        assert!(m1.left_join(&m2).eq([
+            (&1, &'a', None),
+            (&2, &'b', Some(&20)),
+            (&4, &'d', None),
+            (&5, &'e', Some(&50)),
+        ]));
+        assert!(m1.right_join(&m2).eq([
+            (&2, Some(&'b'), &20),
+            (&3, None, &30),
+            (&5, Some(&'e'), &50),
+            (&6, None, &60),
+        ]));
+        assert!(m1.outer_join(&m2).eq([
+            (&1, EitherOrBoth::Left(&'a')),
+            (&2, EitherOrBoth::Both(&'b', &20)),
+            (&3, EitherOrBoth::Right(&30)),
+            (&4, EitherOrBoth::Left(&'d')),
+            (&5, EitherOrBoth::Both(&'e', &50)),
+            (&6, EitherOrBoth::Right(&60)),
+        ]));
+        assert!(m1.semi_join(&m2).eq(&[(2, 'b'), (5, 'e')]));
+        assert!(m1.anti_join(&m2).eq(&[(1, 'a'), (4, 'd')]));
+        assert!(m2.anti_join(&m1).eq(&[(3, 30), (6, 60)]));
+
+        let empty: VecMap<u8, ()> = VecMap::new();
+        assert_eq!(m1.join(&empty).count(), 0);
+        assert_eq!(m1.left_join(&empty).count(), 4);
+        assert_eq!(m1.left_join(&m2).len(), 4);
+        assert_eq!(m1.right_join(&empty).len(), 0);
+        assert_eq!(m1.anti_join(&empty).count(), 4);
+        assert_eq!(EitherOrBoth::<u8, u8>::Both(1, 2).right(), Some(2));
+    }
+
+    #[test]
+    fn asof_join() {
+        let trades: VecMap<u32, char> =
+            VecMap::from_iter([(1, 'a'), (5, 'b'), (10, 'c'), (13, 'd')]);
+        let quotes: VecMap<u32, u32> = VecMap::from_iter([(2, 20), (5, 50), (8, 80), (20, 200)]);
+        let dist = |a: &u32, b: &u32| a.abs_diff(*b);
+        let matched = |iter: AsofJoin<'_, u32, char, u32, u32, _>| {
+            iter.map(|(_, _, m)| m.map(|(&k, _)| k)).collect::<Vec<_>>()
+        };
+
+        let backward = trades.asof_join(&quotes, AsofDirection::Backward, dist);
+        assert_eq!(backward.len(), 4);
+        assert_eq!(matched(backward), [None, Some(5), Some(8), Some(8)]);
+
+        let forward = trades.asof_join(&quotes, AsofDirection::Forward, dist);
+        assert_eq!(matched(forward), [Some(2), Some(5), Some(20), Some(20)]);
+
+        let nearest = trades.asof_join(&quotes, AsofDirection::Nearest, dist);
+        assert_eq!(matched(nearest), [Some(2), Some(5), Some(8), Some(8)]);
+
+        let within = trades
+            .asof_join(&quotes, AsofDirection::Backward, dist)
+            .tolerance(2);
+        assert_eq!(matched(within), [None, Some(5), Some(8), None]);
+
+        let mut iter = trades.asof_join(&quotes, AsofDirection::Nearest, dist);
+        assert_eq!(iter.nth(2), Some((&10, &'c', Some((&8, &80)))));
+
+        let empty: VecMap<u32, u32> = VecMap::new();
+        let none = trades.asof_join(&empty, AsofDirection::Nearest, dist);
+        assert_eq!(matched(none), [None, None, None, None]);
+
+        let matched = |iter: AsofJoin<'_, u32, char, u32, (), _>| {
+            iter.map(|(_, _, m)| m.map(|(&k, _)| k)).collect::<Vec<_>>()
+        };
+        let backward = trades.asof_join_by_direction(&quotes, AsofDirection::Backward);
+        assert_eq!(matched(backward), [None, Some(5), Some(8), Some(8)]);
+        let nearest = trades.asof_join_by_direction(&quotes, AsofDirection::Nearest);
+        assert_eq!(matched(nearest), [Some(2), Some(5), Some(8), Some(8)]);
+    }
+
+    #[test]
+    fn diff() {
+        let old: VecMap<&str, u8> = VecMap::from_iter([("a", 1), ("b", 2), ("c", 3), ("e", 5)]);
+        let new: VecMap<&str, u8> = VecMap::from_iter([("b", 2), ("c", 4), ("d", 4), ("e", 5)]);
+
+        assert!(old.diff(&new).eq([
+            DiffItem::Removed(&"a", &1),
+            DiffItem::Changed(&"c", &3, &4),
+            DiffItem::Added(&"d", &4),
+        ]));
+        assert!(old.diff(&new).with_unchanged().eq([
+            DiffItem::Removed(&"a", &1),
+            DiffItem::Unchanged(&"b", &2),
+            DiffItem::Changed(&"c", &3, &4),
+            DiffItem::Added(&"d", &4),
+            DiffItem::Unchanged(&"e", &5),
+        ]));
+        assert_eq!(old.diff(&old).count(), 0);
+        assert!(VecMap::new()
+            .diff(&old)
+            .all(|item| matches!(item, DiffItem::Added(..))));
+    }
+
+    #[test]
+    fn apply_batch() {
+        let mut m: VecMap<u8, String> = (1..=4).map(|k| (k, k.to_string())).collect();
+        let outcomes = m.apply_batch([
+            BatchOp::Remove(3),
+            BatchOp::Insert(2, "x".into()),
+            BatchOp::Upsert(4, "d".into()),
+            BatchOp::Insert(6, "f".into()),
+            BatchOp::Remove(5),
+            BatchOp::Upsert(0, "z".into()),
+            BatchOp::Upsert(6, "g".into()),
+            BatchOp::Remove(6),
+            BatchOp::Insert(3, "c".into()),
+        ]);
+        assert_eq!(
+            outcomes,
+            [
+                BatchOutcome::Removed("3".into()),
+                BatchOutcome::Occupied("x".into()),
+                BatchOutcome::Replaced("4".into()),
+                BatchOutcome::Inserted,
+                BatchOutcome::NotFound,
+                BatchOutcome::Inserted,
+                BatchOutcome::Replaced("f".into()),
+                BatchOutcome::Removed("g".into()),
+                BatchOutcome::Inserted,
+            ]
+        );
+        assert!(m.iter().eq(&[
+            (0, "z".into()),
+            (1, "1".into()),
+            (2, "2".into()),
+            (3, "c".into()),
+            (4, "d".into()),
+        ]));
+    }
+
+    #[test]
+    fn apply_patch() {
+        let new = || -> VecMap<u8, String> { (1..=4).map(|k| (k, k.to_string())).collect() };
+        let patch = || {
+            vec![
+                PatchOp::Replace {
+                    key: 3,
+                    expected: "3".into(),
+                    new: "c".into(),
+                },
+                PatchOp::Insert(6, "f".into()),
+                PatchOp::Remove(1),
+                PatchOp::Remove(5),
+                PatchOp::Insert(0, "z".into()),
+                PatchOp::Replace {
+                    key: 2,
+                    expected: "x".into(),
+                    new: "b".into(),
+                },
+            ]
+        };
+
+        let mut m = new();
+        let conflicts = m.apply_patch(patch(), PatchMode::BestEffort);
+        assert_eq!(
+            conflicts,
+            [
+                PatchConflict::Mismatch(PatchOp::Replace {
+                    key: 2,
+                    expected: "x".into(),
+                    new: "b".into(),
+                }),
+                PatchConflict::Missing(PatchOp::Remove(5)),
+            ]
+        );
+        assert!(m.iter().eq(&[
+            (0, "z".into()),
+            (2, "2".into()),
+            (3, "c".into()),
+            (4, "4".into()),
+            (6, "f".into()),
+        ]));
+
+        let mut m = new();
+        let conflicts = m.apply_patch(patch(), PatchMode::Atomic);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(m, new());
+
+        let mut m = new();
+        let ops = [
+            PatchOp::Remove(2),
+            PatchOp::Insert(2, "x".into()),
+            PatchOp::Replace {
+                key: 2,
+                expected: "x".into(),
+                new: "y".into(),
+            },
+            PatchOp::Remove(4),
+            PatchOp::Remove(4),
+        ];
+        let conflicts = m.apply_patch(ops, PatchMode::BestEffort);
+        assert_eq!(conflicts, [PatchConflict::Missing(PatchOp::Remove(4))]);
+        assert!(m
+            .iter()
+            .eq(&[(1, "1".into()), (2, "y".into()), (3, "3".into())]));
+    }
+
+    #[test]
+    fn apply_family() {
+        let mut map = VecMap::from_iter([(1, 2), (3, 4), (5, 6)]);
+        let keys = VecSet::from_iter([0, 1, 3, 4, 6]);
+
+        map.apply_mut(&keys, |v| *v *= 10);
+        assert!(map.iter().eq(&[(1, 20), (3, 40), (5, 6)]));
+
+        let mut ans = Vec::new();
+        map.apply_with_key(&keys, |&k, &v| ans.push((k, v)));
+        assert_eq!(ans, [(1, 20), (3, 40)]);
+
+        let mut ans = Vec::new();
+        map.apply_outer(&keys, |lookup| ans.push(lookup));
+        assert_eq!(
+            ans,
+            [
+                KeyLookup::Missing(&0),
+                KeyLookup::Found(&1, &20),
+                KeyLookup::Found(&3, &40),
+                KeyLookup::Missing(&4),
+                KeyLookup::Missing(&6),
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_ops() {
+        let new = || -> VecMap<u8, String> { (1..=6).map(|k| (k, k.to_string())).collect() };
+        let keys = VecSet::from_iter([0, 2, 3, 6, 9]);
+
+        let mut m = new();
+        m.retain_keys(&keys);
+        assert!(m.keys().eq(&[2, 3, 6]));
+
+        let mut m = new();
+        m.remove_keys(&keys);
+        assert!(m.keys().eq(&[1, 4, 5]));
+
+        let mut m = new();
+        let extracted = m.extract(&keys);
+        assert!(m.keys().eq(&[1, 4, 5]));
+        assert!(extracted
+            .iter()
+            .eq(&[(2, "2".into()), (3, "3".into()), (6, "6".into())]));
+
+        let other: VecMap<u8, u32> = VecMap::from_iter([(2, 20), (5, 50), (7, 70)]);
+        let joined = new().intersection_with(&other, |k, v, w| format!("{k}:{v}:{w}"));
+        assert!(joined
+            .iter()
+            .eq(&[(2, "2:2:20".into()), (5, "5:5:50".into())]));
+        assert!(new().difference(&other).keys().eq(&[1, 3, 4, 6]));
+
+        let set = new().keys_set();
+        assert_eq!(set.as_slice(), &[1, 2, 3, 4, 5, 6]);
+        let m = VecMap::from_set(set, |&k| k * 10);
+        assert!(m
+            .iter()
+            .eq(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)]));
     }
 
     #[test]
@@ -1,33 +1,61 @@
+use crate::merge::{merge_grouped, MergeWalk, Whole};
+use crate::vecmap::EitherOrBoth;
+use crate::{Comparator, Natural, UnorderedKeyError};
+
 use core::borrow::Borrow;
 use core::cmp::Ordering;
 use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
 use core::mem;
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+use core::ops::{Bound, Deref, DerefMut, RangeBounds};
 use core::ptr;
 use core::slice;
 
 use alloc::vec;
 use alloc::vec::Vec;
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub struct VecSet<T>(Vec<T>);
+/// A set stored as a sorted vector.
+///
+/// Elements are ordered by the comparator `C`, which defaults to `Ord`.
+/// Operations on two sets require both to be ordered by equivalent
+/// comparators; see [`Comparator`].
+#[derive(Clone)]
+pub struct VecSet<T, C = Natural>(Vec<T>, C);
 
 impl<T> VecSet<T> {
     #[inline]
     #[must_use]
     pub const fn new() -> Self {
-        Self(Vec::new())
+        Self(Vec::new(), Natural)
     }
 
     #[inline]
     #[must_use]
     pub fn from_single(val: T) -> Self {
-        Self(vec![val])
+        Self(vec![val], Natural)
     }
 
     #[inline]
     #[must_use]
     pub fn with_capacity(cap: usize) -> Self {
-        Self(Vec::with_capacity(cap))
+        Self(Vec::with_capacity(cap), Natural)
+    }
+}
+
+impl<T, C> VecSet<T, C> {
+    #[inline]
+    #[must_use]
+    pub const fn with_comparator(cmp: C) -> Self {
+        Self(Vec::new(), cmp)
+    }
+
+    /// Returns the comparator that orders the elements.
+    #[inline]
+    #[must_use]
+    pub fn comparator(&self) -> &C {
+        &self.1
     }
 
     #[inline]
@@ -48,46 +76,520 @@ impl<T> VecSet<T> {
         self.0.as_slice()
     }
 
+    /// Consumes the set and returns its elements as a sorted vector.
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+
+    pub(crate) fn from_vec_unchecked(v: Vec<T>, cmp: C) -> Self {
+        Self(v, cmp)
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<T>, C) {
+        (self.0, self.1)
+    }
+
+    /// Returns the smallest element.
+    #[inline]
+    #[must_use]
+    pub fn first(&self) -> Option<&T> {
+        self.0.first()
+    }
+
+    /// Returns the greatest element.
+    #[inline]
+    #[must_use]
+    pub fn last(&self) -> Option<&T> {
+        self.0.last()
+    }
+
+    /// Removes and returns the smallest element.
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<T> {
+        if self.0.is_empty() {
+            return None;
+        }
+        Some(self.0.remove(0))
+    }
+
+    /// Removes and returns the greatest element.
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.0.pop()
+    }
+
+    /// Keeps only the elements for which `f` returns `true`.
+    #[inline]
+    pub fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+        self.0.retain(f);
+    }
+
+    /// Returns an iterator which removes and yields the elements matching `pred`.
+    ///
+    /// If the iterator is dropped before it is exhausted, the remaining
+    /// elements are retained.
+    #[inline]
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let old_len = self.0.len();
+        unsafe { self.0.set_len(0) };
+        ExtractIf {
+            vec: &mut self.0,
+            idx: 0,
+            del: 0,
+            old_len,
+            pred,
+        }
+    }
+
+    /// Returns the element at position `idx`.
+    #[inline]
+    #[must_use]
+    pub fn get_index(&self, idx: usize) -> Option<&T> {
+        self.0.get(idx)
+    }
+
+    /// Removes the element at `idx`, shifting the following elements down.
+    #[inline]
+    pub fn remove_index(&mut self, idx: usize) -> Option<T> {
+        (idx < self.0.len()).then(|| self.0.remove(idx))
+    }
+
+    /// Returns the lower median element.
+    #[inline]
+    #[must_use]
+    pub fn median(&self) -> Option<&T> {
+        let idx = self.len().checked_sub(1)? / 2;
+        Some(unsafe { self.0.get_unchecked(idx) })
+    }
+
+    /// Returns the element at rank `floor(q * (len - 1))`.
+    ///
+    /// Returns `None` if the set is empty or `q` is not in `0.0..=1.0`,
+    /// which includes NaN.
+    #[inline]
+    #[must_use]
+    pub fn quantile(&self, q: f64) -> Option<&T> {
+        if !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let last = self.len().checked_sub(1)?;
+        let idx = (q * last as f64) as usize;
+        Some(unsafe { self.0.get_unchecked(idx.min(last)) })
+    }
+
     #[inline]
     #[must_use]
     pub fn iter(&self) -> Iter<'_, T> {
         Iter(self.0.as_slice().iter())
     }
 
+    /// Binary searches the elements with `f`, which orders an element against the target.
     #[inline]
-    #[must_use]
-    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
-        IterMut(self.0.as_mut_slice().iter_mut())
+    pub fn binary_search_by(&self, f: impl FnMut(&T) -> Ordering) -> Result<usize, usize> {
+        self.0.binary_search_by(f)
+    }
+
+    /// Returns the element that `f` orders as `Equal`, found by binary search.
+    #[inline]
+    pub fn get_by(&self, f: impl FnMut(&T) -> Ordering) -> Option<&T> {
+        let idx = self.binary_search_by(f).ok()?;
+        Some(unsafe { self.0.get_unchecked(idx) })
+    }
+
+    /// Returns the index of the first element for which `pred` is false.
+    ///
+    /// The elements must be partitioned by `pred`, true before false.
+    #[inline]
+    pub fn partition_point(&self, pred: impl FnMut(&T) -> bool) -> usize {
+        self.0.partition_point(pred)
+    }
+
+    /// Returns the elements that `f` maps to `Equal`.
+    ///
+    /// `f` must return `Less` below the range and `Greater` above it.
+    #[inline]
+    pub fn range_by(&self, mut f: impl FnMut(&T) -> Ordering) -> Range<'_, T> {
+        let start = self.partition_point(|x| f(x).is_lt());
+        let len = self.0[start..].partition_point(|x| f(x).is_le());
+        Range(self.0[start..start + len].iter())
     }
 }
 
 impl<T: Ord> VecSet<T> {
     #[inline]
     #[must_use]
-    pub fn from_vec(mut v: Vec<T>) -> Self {
-        v.sort_unstable();
-        v.dedup_by(|x, first| x == first);
-        Self(v)
+    pub fn from_vec(v: Vec<T>) -> Self {
+        Self::from_vec_with_comparator(v, Natural)
+    }
+}
+
+impl<T, C: Comparator<T>> VecSet<T, C> {
+    /// Builds a set from elements in any order, ordered by `cmp`.
+    ///
+    /// If several elements are equal, only one of them is kept.
+    #[inline]
+    #[must_use]
+    pub fn from_vec_with_comparator(mut v: Vec<T>, cmp: C) -> Self {
+        v.sort_unstable_by(|lhs, rhs| cmp.compare(lhs, rhs));
+        v.dedup_by(|x, first| cmp.compare(x, first).is_eq());
+        Self(v, cmp)
     }
 
     fn search<Q>(&self, val: &Q) -> Result<usize, usize>
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        self.0.binary_search_by(|probe| self.1.compare(probe, val))
+    }
+
+    fn search_range<Q, R>(&self, range: &R) -> (usize, usize)
+    where
+        C: Comparator<T, Q> + Comparator<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Excluded(s), Bound::Excluded(e)) if self.1.compare(s, e).is_eq() => {
+                panic!("range start and end are equal and excluded in VecSet")
+            }
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+                if self.1.compare(s, e).is_gt() =>
+            {
+                panic!("range start is greater than range end in VecSet")
+            }
+            _ => {}
+        }
+
+        let start = self.search_start(range.start_bound());
+        let end = self.search_end(range.end_bound());
+        (start, end.max(start))
+    }
+
+    /// Returns the position of the element equal to `val`, if there is one.
+    #[inline]
+    #[must_use]
+    pub fn index_of<Q>(&self, val: &Q) -> Option<usize>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
-        self.0.binary_search_by(|probe| probe.borrow().cmp(val))
+        self.search(val).ok()
+    }
+
+    /// Returns the number of elements less than `val`.
+    #[inline]
+    #[must_use]
+    pub fn rank<Q>(&self, val: &Q) -> usize
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        self.search_lower(val)
     }
 
     #[inline]
     #[must_use]
     pub fn contains<Q>(&self, val: &Q) -> bool
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
         self.search(val).is_ok()
     }
 
+    /// Returns a guard which gives mutable access to the elements.
+    ///
+    /// When the guard is dropped, the elements are sorted and deduplicated
+    /// again if their order was broken. If the guard is leaked, the elements
+    /// stay in whatever order they were left in.
+    #[inline]
+    #[must_use]
+    pub fn as_mut_slice(&mut self) -> SliceMut<'_, T, C> {
+        SliceMut {
+            elems: &mut self.0,
+            cmp: &self.1,
+        }
+    }
+
+    /// Returns the element equal to `val`, if there is one.
+    #[inline]
+    #[must_use]
+    pub fn get<Q>(&self, val: &Q) -> Option<&T>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search(val).ok()?;
+        Some(unsafe { self.0.get_unchecked(idx) })
+    }
+
+    fn search_lower<Q>(&self, val: &Q) -> usize
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        match self.search(val) {
+            Ok(idx) | Err(idx) => idx,
+        }
+    }
+
+    fn search_upper<Q>(&self, val: &Q) -> usize
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        match self.search(val) {
+            Ok(idx) => idx + 1,
+            Err(idx) => idx,
+        }
+    }
+
+    fn search_start<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        match bound {
+            Bound::Included(val) => self.search_lower(val),
+            Bound::Excluded(val) => self.search_upper(val),
+            Bound::Unbounded => 0,
+        }
+    }
+
+    fn search_end<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        match bound {
+            Bound::Included(val) => self.search_upper(val),
+            Bound::Excluded(val) => self.search_lower(val),
+            Bound::Unbounded => self.len(),
+        }
+    }
+
+    /// Returns the position of the first element that is not less than `val`,
+    /// along with that element.
+    #[inline]
+    #[must_use]
+    pub fn lower_bound<Q>(&self, val: &Q) -> (usize, Option<&T>)
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search_lower(val);
+        (idx, self.0.get(idx))
+    }
+
+    /// Returns the position of the first element that is greater than `val`,
+    /// along with that element.
+    #[inline]
+    #[must_use]
+    pub fn upper_bound<Q>(&self, val: &Q) -> (usize, Option<&T>)
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search_upper(val);
+        (idx, self.0.get(idx))
+    }
+
+    /// Returns the greatest element less than or equal to `val`.
+    #[inline]
+    #[must_use]
+    pub fn floor<Q>(&self, val: &Q) -> Option<&T>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search_upper(val).checked_sub(1)?;
+        Some(unsafe { self.0.get_unchecked(idx) })
+    }
+
+    /// Returns the smallest element greater than or equal to `val`.
+    #[inline]
+    #[must_use]
+    pub fn ceiling<Q>(&self, val: &Q) -> Option<&T>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        self.lower_bound(val).1
+    }
+
+    /// Returns the greatest element strictly less than `val`.
+    #[inline]
+    #[must_use]
+    pub fn predecessor<Q>(&self, val: &Q) -> Option<&T>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        let idx = self.search_lower(val).checked_sub(1)?;
+        Some(unsafe { self.0.get_unchecked(idx) })
+    }
+
+    /// Returns the smallest element strictly greater than `val`.
+    #[inline]
+    #[must_use]
+    pub fn successor<Q>(&self, val: &Q) -> Option<&T>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        self.upper_bound(val).1
+    }
+
+    /// Returns an iterator over the elements in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    #[must_use]
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        C: Comparator<T, Q> + Comparator<Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        Range(self.0[start..end].iter())
+    }
+
+    fn upper_bound_index<Q>(&self, bound: Bound<&Q>) -> usize
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+    {
+        self.search_end(bound).checked_sub(1).unwrap_or(self.len())
+    }
+
+    /// Returns a cursor at the first element above `bound`, or at the ghost
+    /// position if there is none.
+    #[inline]
+    #[must_use]
+    pub fn lower_bound_cursor<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, T>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.search_start(bound);
+        Cursor {
+            elems: &self.0,
+            index,
+        }
+    }
+
+    /// Like [`lower_bound_cursor`](Self::lower_bound_cursor), but with editing operations.
+    #[inline]
+    pub fn lower_bound_cursor_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, T, C>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.search_start(bound);
+        CursorMut {
+            elems: &mut self.0,
+            cmp: &self.1,
+            index,
+        }
+    }
+
+    /// Returns a cursor at the last element below `bound`, or at the ghost
+    /// position if there is none.
+    #[inline]
+    #[must_use]
+    pub fn upper_bound_cursor<Q>(&self, bound: Bound<&Q>) -> Cursor<'_, T>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.upper_bound_index(bound);
+        Cursor {
+            elems: &self.0,
+            index,
+        }
+    }
+
+    /// Like [`upper_bound_cursor`](Self::upper_bound_cursor), but with editing operations.
+    #[inline]
+    pub fn upper_bound_cursor_mut<Q>(&mut self, bound: Bound<&Q>) -> CursorMut<'_, T, C>
+    where
+        C: Comparator<T, Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.upper_bound_index(bound);
+        CursorMut {
+            elems: &mut self.0,
+            cmp: &self.1,
+            index,
+        }
+    }
+
+    /// Removes the elements in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    pub fn remove_range<Q, R>(&mut self, range: R)
+    where
+        C: Comparator<T, Q> + Comparator<Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        self.0.drain(start..end);
+    }
+
+    /// Removes the elements in `range` and returns them as an iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    pub fn drain_range<Q, R>(&mut self, range: R) -> Drain<'_, T>
+    where
+        C: Comparator<T, Q> + Comparator<Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        Drain(self.0.drain(start..end))
+    }
+
+    /// Keeps only the elements in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range start is greater than its end, or if both are equal and excluded.
+    #[inline]
+    pub fn retain_range<Q, R>(&mut self, range: R)
+    where
+        C: Comparator<T, Q> + Comparator<Q>,
+        Q: ?Sized,
+        T: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = self.search_range(&range);
+        self.0.truncate(end);
+        self.0.drain(..start);
+    }
+
     #[inline]
     #[must_use]
     pub fn insert(&mut self, val: T) -> Option<T> {
@@ -107,8 +609,8 @@ impl<T: Ord> VecSet<T> {
     #[must_use]
     pub fn remove<Q>(&mut self, val: &Q) -> Option<T>
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
         match self.search(val) {
             Ok(idx) => Some(self.0.remove(idx)),
@@ -116,6 +618,71 @@ impl<T: Ord> VecSet<T> {
         }
     }
 
+    fn merge_sorted_vec(&mut self, rhs: Vec<T>, mut f: impl FnMut(T, T) -> T) {
+        let cmp = &self.1;
+        merge_grouped(
+            &mut self.0,
+            rhs,
+            |x, y| cmp.compare(x, y),
+            |_, _| false,
+            |cur, mut group| match (cur, group.next()) {
+                (Some(x), Some(y)) => Some(f(x, y)),
+                (None, val) | (val, None) => val,
+            },
+        );
+    }
+
+    /// Applies a batch of operations in a single linear merge.
+    ///
+    /// Operations on equal values are applied in the order they are given.
+    /// Returns one outcome per operation, in the order they are given.
+    ///
+    /// If the comparator panics, `self` stays sorted with only part of the
+    /// batch applied.
+    #[inline]
+    pub fn apply_batch(
+        &mut self,
+        ops: impl IntoIterator<Item = BatchOp<T>>,
+    ) -> Vec<BatchOutcome<T>> {
+        let mut ops: Vec<(usize, BatchOp<T>)> = ops.into_iter().enumerate().collect();
+        ops.sort_by(|lhs, rhs| self.1.compare(lhs.1.value(), rhs.1.value()));
+
+        let mut outcomes: Vec<BatchOutcome<T>> = Vec::with_capacity(ops.len());
+        outcomes.resize_with(ops.len(), || BatchOutcome::NotFound);
+
+        let cmp = &self.1;
+        merge_grouped(
+            &mut self.0,
+            ops,
+            |x, (_, op)| cmp.compare(x, op.value()),
+            |(_, lhs), (_, rhs)| cmp.compare(lhs.value(), rhs.value()).is_eq(),
+            |mut cur, group| {
+                for (idx, op) in group {
+                    outcomes[idx] = match op {
+                        BatchOp::Insert(val) => match cur {
+                            Some(_) => BatchOutcome::Occupied(val),
+                            None => {
+                                cur = Some(val);
+                                BatchOutcome::Inserted
+                            }
+                        },
+                        BatchOp::Upsert(val) => match cur.replace(val) {
+                            Some(prev) => BatchOutcome::Replaced(prev),
+                            None => BatchOutcome::Inserted,
+                        },
+                        BatchOp::Remove(_) => match cur.take() {
+                            Some(prev) => BatchOutcome::Removed(prev),
+                            None => BatchOutcome::NotFound,
+                        },
+                    };
+                }
+                cur
+            },
+        );
+        outcomes
+    }
+
+    /// Adds the elements of `other` to `self`.
     #[inline]
     pub fn union_copied_inplace(&mut self, other: &Self)
     where
@@ -134,7 +701,7 @@ impl<T: Ord> VecSet<T> {
             let e1 = p1.add(lhs.len());
             let e2 = p2.add(rhs.len());
 
-            let end = raw_union_copied(p1, p2, p3, e1, e2);
+            let end = raw_union_copied(&self.1, p1, p2, p3, e1, e2);
 
             let dst = lhs.as_mut_ptr();
             let src = dst.add(lhs.len());
@@ -144,11 +711,13 @@ impl<T: Ord> VecSet<T> {
         }
     }
 
+    /// Returns the elements in `self` or `other`, copied into a new set.
     #[inline]
     #[must_use]
     pub fn union_copied(&self, other: &Self) -> Self
     where
         T: Copy,
+        C: Clone,
     {
         let lhs = &self.0;
         let rhs = &other.0;
@@ -163,106 +732,1155 @@ impl<T: Ord> VecSet<T> {
             let e1 = p1.add(lhs.len());
             let e2 = p2.add(rhs.len());
 
-            let end = raw_union_copied(p1, p2, p3, e1, e2);
+            let end = raw_union_copied(&self.1, p1, p2, p3, e1, e2);
             let cnt = end.offset_from(p3) as usize;
             ans.set_len(cnt);
         }
 
-        Self(ans)
+        Self(ans, self.1.clone())
+    }
+
+    /// Returns the elements in both `self` and `other`, copied into a new set.
+    #[inline]
+    #[must_use]
+    pub fn intersection_copied(&self, other: &Self) -> Self
+    where
+        T: Copy,
+        C: Clone,
+    {
+        let lhs = &self.0;
+        let rhs = &other.0;
+
+        let ans_cap = lhs.len().min(rhs.len());
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        unsafe {
+            let p1 = lhs.as_ptr();
+            let p2 = rhs.as_ptr();
+            let p3 = ans.as_mut_ptr();
+            let e1 = p1.add(lhs.len());
+            let e2 = p2.add(rhs.len());
+
+            let end = raw_intersection_copied(&self.1, p1, p2, p3, e1, e2);
+            let cnt = end.offset_from(p3) as usize;
+            ans.set_len(cnt)
+        }
+
+        Self(ans, self.1.clone())
+    }
+
+    /// Removes the elements of `other` from `self`.
+    #[inline]
+    pub fn difference_copied_inplace(&mut self, other: &Self)
+    where
+        T: Copy,
+    {
+        let lhs = &mut self.0;
+        let rhs = &other.0;
+
+        let ans_cap = lhs.len();
+        lhs.reserve(ans_cap);
+
+        unsafe {
+            let p1 = lhs.as_ptr();
+            let p2 = rhs.as_ptr();
+            let p3 = lhs.as_mut_ptr().add(lhs.len());
+            let e1 = p1.add(lhs.len());
+            let e2 = p2.add(rhs.len());
+
+            let end = raw_difference_copied(&self.1, p1, p2, p3, e1, e2);
+
+            let dst = lhs.as_mut_ptr();
+            let src = dst.add(lhs.len());
+            let cnt = end.offset_from(src) as usize;
+            ptr::copy(src, dst, cnt);
+            lhs.set_len(cnt)
+        }
+    }
+
+    /// Keeps only the elements of `self` that are also in `other`.
+    #[inline]
+    pub fn intersection_inplace(&mut self, other: &Self) {
+        let mut rhs = other.0.as_slice();
+        self.0.retain(|x| {
+            while let [y, ys @ ..] = rhs {
+                match self.1.compare(y, x) {
+                    Ordering::Less => rhs = ys,
+                    Ordering::Greater => break,
+                    Ordering::Equal => {
+                        rhs = ys;
+                        return true;
+                    }
+                }
+            }
+            false
+        });
+    }
+
+    /// Returns the elements in `self` but not in `other`, copied into a new set.
+    #[inline]
+    #[must_use]
+    pub fn difference_copied(&self, other: &Self) -> Self
+    where
+        T: Copy,
+        C: Clone,
+    {
+        let lhs = &self.0;
+        let rhs = &other.0;
+
+        let ans_cap = lhs.len();
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        unsafe {
+            let p1 = lhs.as_ptr();
+            let p2 = rhs.as_ptr();
+            let p3 = ans.as_mut_ptr();
+            let e1 = p1.add(lhs.len());
+            let e2 = p2.add(rhs.len());
+
+            let end = raw_difference_copied(&self.1, p1, p2, p3, e1, e2);
+            let cnt = end.offset_from(p3) as usize;
+            ans.set_len(cnt)
+        }
+
+        Self(ans, self.1.clone())
+    }
+
+    /// Returns the elements in exactly one of `self` and `other`, copied
+    /// into a new set.
+    #[inline]
+    #[must_use]
+    pub fn symmetric_difference_copied(&self, other: &Self) -> Self
+    where
+        T: Copy,
+        C: Clone,
+    {
+        let lhs = &self.0;
+        let rhs = &other.0;
+
+        let ans_cap = lhs.len().checked_add(rhs.len()).unwrap();
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        unsafe {
+            let p1 = lhs.as_ptr();
+            let p2 = rhs.as_ptr();
+            let p3 = ans.as_mut_ptr();
+            let e1 = p1.add(lhs.len());
+            let e2 = p2.add(rhs.len());
+
+            let end = raw_symmetric_difference_copied(&self.1, p1, p2, p3, e1, e2);
+            let cnt = end.offset_from(p3) as usize;
+            ans.set_len(cnt)
+        }
+
+        Self(ans, self.1.clone())
+    }
+
+    /// Keeps the elements in exactly one of `self` and `other`.
+    #[inline]
+    pub fn symmetric_difference_copied_inplace(&mut self, other: &Self)
+    where
+        T: Copy,
+    {
+        let lhs = &mut self.0;
+        let rhs = &other.0;
+
+        let ans_cap = lhs.len().checked_add(rhs.len()).unwrap();
+        lhs.reserve(ans_cap);
+
+        unsafe {
+            let p1 = lhs.as_ptr();
+            let p2 = rhs.as_ptr();
+            let p3 = lhs.as_mut_ptr().add(lhs.len());
+            let e1 = p1.add(lhs.len());
+            let e2 = p2.add(rhs.len());
+
+            let end = raw_symmetric_difference_copied(&self.1, p1, p2, p3, e1, e2);
+
+            let dst = lhs.as_mut_ptr();
+            let src = dst.add(lhs.len());
+            let cnt = end.offset_from(src) as usize;
+            ptr::copy(src, dst, cnt);
+            lhs.set_len(cnt)
+        }
+    }
+
+    /// Iterates over the elements in `self` or `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn union_iter<'a>(&'a self, other: &'a Self) -> Union<'a, T, C> {
+        Union(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the elements in both `self` and `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn intersection_iter<'a>(&'a self, other: &'a Self) -> Intersection<'a, T, C> {
+        Intersection(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the elements in `self` but not in `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn difference_iter<'a>(&'a self, other: &'a Self) -> Difference<'a, T, C> {
+        Difference(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Iterates over the elements in exactly one of `self` and `other`, in order.
+    #[inline]
+    #[must_use]
+    pub fn symmetric_difference_iter<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> SymmetricDifference<'a, T, C> {
+        SymmetricDifference(MergeWalk::new(&self.0, &other.0, &self.1))
+    }
+
+    /// Returns `true` if every element of `self` is in `other`.
+    #[inline]
+    #[must_use]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        other.contains_all(self)
+    }
+
+    /// Returns `true` if every element of `other` is in `self`.
+    #[inline]
+    #[must_use]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        self.contains_all(other)
+    }
+
+    /// Returns `true` if `self` and `other` have no elements in common.
+    #[inline]
+    #[must_use]
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        !self.contains_any(other)
+    }
+
+    /// Returns `true` if every value in `vals` is in `self`.
+    #[inline]
+    #[must_use]
+    pub fn contains_all<Q>(&self, vals: &VecSet<Q, C>) -> bool
+    where
+        C: Comparator<T, Q>,
+    {
+        if vals.len() > self.len() {
+            return false;
+        }
+
+        let mut lhs = self.0.as_slice();
+        for y in &vals.0 {
+            loop {
+                let [x, xs @ ..] = lhs else { return false };
+                lhs = xs;
+                match self.1.compare(x, y) {
+                    Ordering::Less => {}
+                    Ordering::Greater => return false,
+                    Ordering::Equal => break,
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns `true` if any value in `vals` is in `self`.
+    #[inline]
+    #[must_use]
+    pub fn contains_any<Q>(&self, vals: &VecSet<Q, C>) -> bool
+    where
+        C: Comparator<T, Q>,
+    {
+        let mut lhs = self.0.as_slice();
+        let mut rhs = vals.0.as_slice();
+        while let ([x, xs @ ..], [y, ys @ ..]) = (lhs, rhs) {
+            match self.1.compare(x, y) {
+                Ordering::Less => lhs = xs,
+                Ordering::Greater => rhs = ys,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    /// Adds clones of the elements of `other` to `self`.
+    ///
+    /// Of two equal elements, the one from `self` is kept. If `clone` or the
+    /// comparator panics, `self` stays sorted with only part of `other` added.
+    #[inline]
+    pub fn union_cloned_inplace(&mut self, other: &Self)
+    where
+        T: Clone,
+    {
+        let cmp = &self.1;
+        merge_grouped(
+            &mut self.0,
+            other.0.iter().collect(),
+            |x, y| cmp.compare(x, y),
+            |_, _| false,
+            |cur, mut group| cur.or_else(|| group.next().cloned()),
+        );
+    }
+
+    /// Returns the elements in `self` or `other`, cloned into a new set.
+    #[inline]
+    #[must_use]
+    pub fn union_cloned(&self, other: &Self) -> Self
+    where
+        T: Clone,
+        C: Clone,
+    {
+        let mut lhs = self.0.as_slice();
+        let mut rhs = other.0.as_slice();
+
+        let ans_cap = lhs.len().checked_add(rhs.len()).unwrap();
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        while let ([x, xs @ ..], [y, ys @ ..]) = (lhs, rhs) {
+            match self.1.compare(x, y) {
+                Ordering::Less => {
+                    ans.push(x.clone());
+                    lhs = xs;
+                }
+                Ordering::Greater => {
+                    ans.push(y.clone());
+                    rhs = ys;
+                }
+                Ordering::Equal => {
+                    ans.push(x.clone());
+                    lhs = xs;
+                    rhs = ys;
+                }
+            }
+        }
+        ans.extend_from_slice(lhs);
+        ans.extend_from_slice(rhs);
+
+        Self(ans, self.1.clone())
+    }
+
+    /// Returns the elements in both `self` and `other`, cloned into a new set.
+    #[inline]
+    #[must_use]
+    pub fn intersection_cloned(&self, other: &Self) -> Self
+    where
+        T: Clone,
+        C: Clone,
+    {
+        let mut lhs = self.0.as_slice();
+        let mut rhs = other.0.as_slice();
+
+        let ans_cap = lhs.len().min(rhs.len());
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        while let ([x, xs @ ..], [y, ys @ ..]) = (lhs, rhs) {
+            match self.1.compare(x, y) {
+                Ordering::Less => lhs = xs,
+                Ordering::Greater => rhs = ys,
+                Ordering::Equal => {
+                    ans.push(x.clone());
+                    lhs = xs;
+                    rhs = ys;
+                }
+            }
+        }
+
+        Self(ans, self.1.clone())
+    }
+
+    /// Returns the elements in `self` but not in `other`, cloned into a new set.
+    #[inline]
+    #[must_use]
+    pub fn difference_cloned(&self, other: &Self) -> Self
+    where
+        T: Clone,
+        C: Clone,
+    {
+        let mut lhs = self.0.as_slice();
+        let mut rhs = other.0.as_slice();
+
+        let ans_cap = lhs.len();
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        while let ([x, xs @ ..], [y, ys @ ..]) = (lhs, rhs) {
+            match self.1.compare(x, y) {
+                Ordering::Less => {
+                    ans.push(x.clone());
+                    lhs = xs;
+                }
+                Ordering::Greater => rhs = ys,
+                Ordering::Equal => {
+                    lhs = xs;
+                    rhs = ys;
+                }
+            }
+        }
+        ans.extend_from_slice(lhs);
+
+        Self(ans, self.1.clone())
+    }
+
+    /// Removes the elements of `other` from `self`.
+    #[inline]
+    pub fn difference_inplace(&mut self, other: &Self) {
+        let mut rhs = other.0.as_slice();
+        self.0.retain(|x| {
+            while let [y, ys @ ..] = rhs {
+                match self.1.compare(y, x) {
+                    Ordering::Less => rhs = ys,
+                    Ordering::Greater => break,
+                    Ordering::Equal => {
+                        rhs = ys;
+                        return false;
+                    }
+                }
+            }
+            true
+        });
+    }
+
+    /// Returns the elements in `self` or `other`.
+    ///
+    /// Of two equal elements, the one from `self` is kept.
+    #[inline]
+    #[must_use]
+    pub fn union(mut self, other: Self) -> Self {
+        self.merge_sorted_vec(other.0, |x, _| x);
+        self
+    }
+
+    /// Returns the elements in both `self` and `other`.
+    #[inline]
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        let ans_cap = self.0.len().min(other.0.len());
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        let mut lhs = self.0.into_iter().peekable();
+        let mut rhs = other.0.into_iter().peekable();
+        while let (Some(x), Some(y)) = (lhs.peek(), rhs.peek()) {
+            match self.1.compare(x, y) {
+                Ordering::Less => {
+                    lhs.next();
+                }
+                Ordering::Greater => {
+                    rhs.next();
+                }
+                Ordering::Equal => {
+                    ans.extend(lhs.next());
+                    rhs.next();
+                }
+            }
+        }
+
+        Self(ans, self.1)
+    }
+
+    /// Returns the elements in `self` but not in `other`.
+    #[inline]
+    #[must_use]
+    pub fn difference(mut self, other: Self) -> Self {
+        self.difference_inplace(&other);
+        self
+    }
+
+    /// Returns the elements in exactly one of `self` and `other`.
+    #[inline]
+    #[must_use]
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        let ans_cap = self.0.len().checked_add(other.0.len()).unwrap();
+        let mut ans = Vec::with_capacity(ans_cap);
+
+        let mut lhs = self.0.into_iter().peekable();
+        let mut rhs = other.0.into_iter().peekable();
+        while let (Some(x), Some(y)) = (lhs.peek(), rhs.peek()) {
+            match self.1.compare(x, y) {
+                Ordering::Less => ans.extend(lhs.next()),
+                Ordering::Greater => ans.extend(rhs.next()),
+                Ordering::Equal => {
+                    lhs.next();
+                    rhs.next();
+                }
+            }
+        }
+        ans.extend(lhs);
+        ans.extend(rhs);
+
+        Self(ans, self.1)
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for VecSet<T> {
+    #[inline]
+    fn from(v: Vec<T>) -> Self {
+        Self::from_vec(v)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for VecSet<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+    }
+}
+
+/// Elements from the iterator replace equal elements already in the set.
+/// If the iterator yields equal elements more than once, the last one wins.
+/// If the comparator panics, the set stays sorted with only part of the
+/// iterator merged.
+impl<T, C: Comparator<T>> Extend<T> for VecSet<T, C> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut batch: Vec<T> = iter.into_iter().collect();
+        batch.reverse();
+        batch.sort_by(|lhs, rhs| self.1.compare(lhs, rhs));
+        batch.dedup_by(|x, first| self.1.compare(x, first).is_eq());
+        self.merge_sorted_vec(batch, |_, y| y);
+    }
+}
+
+impl<'a, T: Copy, C: Comparator<T>> Extend<&'a T> for VecSet<T, C> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// Returns the union of two sets.
+impl<T: Copy, C: Comparator<T> + Clone> BitOr<&VecSet<T, C>> for &VecSet<T, C> {
+    type Output = VecSet<T, C>;
+
+    #[inline]
+    fn bitor(self, rhs: &VecSet<T, C>) -> Self::Output {
+        self.union_copied(rhs)
+    }
+}
+
+/// Returns the intersection of two sets.
+impl<T: Copy, C: Comparator<T> + Clone> BitAnd<&VecSet<T, C>> for &VecSet<T, C> {
+    type Output = VecSet<T, C>;
+
+    #[inline]
+    fn bitand(self, rhs: &VecSet<T, C>) -> Self::Output {
+        self.intersection_copied(rhs)
+    }
+}
+
+/// Returns the difference of two sets.
+impl<T: Copy, C: Comparator<T> + Clone> Sub<&VecSet<T, C>> for &VecSet<T, C> {
+    type Output = VecSet<T, C>;
+
+    #[inline]
+    fn sub(self, rhs: &VecSet<T, C>) -> Self::Output {
+        self.difference_copied(rhs)
+    }
+}
+
+/// Returns the symmetric difference of two sets.
+impl<T: Copy, C: Comparator<T> + Clone> BitXor<&VecSet<T, C>> for &VecSet<T, C> {
+    type Output = VecSet<T, C>;
+
+    #[inline]
+    fn bitxor(self, rhs: &VecSet<T, C>) -> Self::Output {
+        self.symmetric_difference_copied(rhs)
+    }
+}
+
+/// Adds the elements of another set.
+impl<T: Copy, C: Comparator<T>> BitOrAssign<&VecSet<T, C>> for VecSet<T, C> {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: &VecSet<T, C>) {
+        self.union_copied_inplace(rhs);
+    }
+}
+
+/// Keeps only the elements also in another set.
+impl<T, C: Comparator<T>> BitAndAssign<&VecSet<T, C>> for VecSet<T, C> {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: &VecSet<T, C>) {
+        self.intersection_inplace(rhs);
+    }
+}
+
+/// Removes the elements of another set.
+impl<T, C: Comparator<T>> SubAssign<&VecSet<T, C>> for VecSet<T, C> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &VecSet<T, C>) {
+        self.difference_inplace(rhs);
+    }
+}
+
+/// Keeps the elements in exactly one of the two sets.
+impl<T: Copy, C: Comparator<T>> BitXorAssign<&VecSet<T, C>> for VecSet<T, C> {
+    #[inline]
+    fn bitxor_assign(&mut self, rhs: &VecSet<T, C>) {
+        self.symmetric_difference_copied_inplace(rhs);
+    }
+}
+
+impl<T, C: Default> Default for VecSet<T, C> {
+    #[inline]
+    fn default() -> Self {
+        Self(Vec::new(), C::default())
+    }
+}
+
+/// Compares the elements in order. The comparators are not compared.
+impl<T: PartialEq, C> PartialEq for VecSet<T, C> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq, C> Eq for VecSet<T, C> {}
+
+/// Compares the elements lexicographically, ignoring the comparators.
+impl<T: PartialOrd, C> PartialOrd for VecSet<T, C> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+/// Compares the elements lexicographically, ignoring the comparators.
+impl<T: Ord, C> Ord for VecSet<T, C> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+/// Hashes the elements in order, ignoring the comparator.
+impl<T: Hash, C> Hash for VecSet<T, C> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for VecSet<T, C> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.iter()).finish()
+    }
+}
+
+/// An operation applied by [`VecSet::apply_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp<T> {
+    /// Inserts the value if no equal element is present.
+    Insert(T),
+    /// Inserts the value, replacing an equal element if present.
+    Upsert(T),
+    /// Removes the element equal to the value.
+    Remove(T),
+}
+
+impl<T> BatchOp<T> {
+    /// Returns the value the operation applies to.
+    #[inline]
+    #[must_use]
+    pub fn value(&self) -> &T {
+        match self {
+            BatchOp::Insert(val) | BatchOp::Upsert(val) | BatchOp::Remove(val) => val,
+        }
+    }
+}
+
+/// The result of a [`BatchOp`], returned by [`VecSet::apply_batch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOutcome<T> {
+    /// No equal element was present and the value has been inserted.
+    Inserted,
+    /// An equal element was present, so the value of an `Insert` is handed back.
+    Occupied(T),
+    /// The value of an `Upsert` has replaced this previous element.
+    Replaced(T),
+    /// This element has been removed.
+    Removed(T),
+    /// No element was equal to the value of a `Remove`.
+    NotFound,
+}
+
+pub struct Iter<'a, T>(slice::Iter<'a, T>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, T, C> IntoIterator for &'a VecSet<T, C> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Mutable access to the elements of a `VecSet`, created by [`VecSet::as_mut_slice`].
+///
+/// The set is re-sorted and deduplicated when this is dropped.
+pub struct SliceMut<'a, T, C: Comparator<T> = Natural> {
+    elems: &'a mut Vec<T>,
+    cmp: &'a C,
+}
+
+impl<T, C: Comparator<T>> Deref for SliceMut<'_, T, C> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.elems.as_slice()
+    }
+}
+
+impl<T, C: Comparator<T>> DerefMut for SliceMut<'_, T, C> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.elems.as_mut_slice()
+    }
+}
+
+impl<T, C: Comparator<T>> Drop for SliceMut<'_, T, C> {
+    #[inline]
+    fn drop(&mut self) {
+        let (v, cmp) = (&mut *self.elems, self.cmp);
+        if v.windows(2).all(|w| cmp.compare(&w[0], &w[1]).is_lt()) {
+            return;
+        }
+        v.sort_unstable_by(|lhs, rhs| cmp.compare(lhs, rhs));
+        v.dedup_by(|x, first| cmp.compare(x, first).is_eq());
+    }
+}
+
+pub struct IntoIter<T>(vec::IntoIter<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T: Clone> Clone for IntoIter<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T, C> IntoIterator for VecSet<T, C> {
+    type Item = T;
+
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+/// An iterator over a range of elements, created by [`VecSet::range`] and [`VecSet::range_by`].
+pub struct Range<'a, T>(slice::Iter<'a, T>);
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Range<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Range<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<T> FusedIterator for Range<'_, T> {}
+
+impl<T> Clone for Range<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// A draining iterator over a range of elements, created by [`VecSet::drain_range`].
+pub struct Drain<'a, T>(vec::Drain<'a, T>);
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+/// An iterator that removes the elements matching a predicate, created by
+/// [`VecSet::extract_if`].
+pub struct ExtractIf<'a, T, F> {
+    vec: &'a mut Vec<T>,
+    idx: usize,
+    del: usize,
+    old_len: usize,
+    pred: F,
+}
+
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while self.idx < self.old_len {
+                let base = self.vec.as_mut_ptr();
+                let cur = base.add(self.idx);
+                let drained = (self.pred)(&*cur);
+                self.idx += 1;
+                if drained {
+                    self.del += 1;
+                    return Some(ptr::read(cur));
+                }
+                if self.del > 0 {
+                    let dst = cur.sub(self.del);
+                    ptr::copy_nonoverlapping(cur, dst, 1);
+                }
+            }
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.old_len - self.idx))
+    }
+}
+
+impl<T, F> Drop for ExtractIf<'_, T, F> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            if self.idx < self.old_len && self.del > 0 {
+                let src = self.vec.as_mut_ptr().add(self.idx);
+                let dst = src.sub(self.del);
+                ptr::copy(src, dst, self.old_len - self.idx);
+            }
+            self.vec.set_len(self.old_len - self.del);
+        }
+    }
+}
+
+/// A cursor over a `VecSet`.
+///
+/// The cursor points at an element or at the ghost position past the last
+/// element. Moving past either end wraps through the ghost position.
+pub struct Cursor<'a, T> {
+    elems: &'a [T],
+    index: usize,
+}
+
+impl<T> Clone for Cursor<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Cursor {
+            elems: self.elems,
+            index: self.index,
+        }
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// Returns the index of the current element, or `None` at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> Option<usize> {
+        (self.index < self.elems.len()).then_some(self.index)
+    }
+
+    /// Returns the current element, or `None` at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn current(&self) -> Option<&'a T> {
+        self.elems.get(self.index)
     }
 
+    /// Returns the element after the current one, or the first element at the ghost position.
     #[inline]
     #[must_use]
-    pub fn intersection_copied(&self, other: &Self) -> Self
-    where
-        T: Copy,
-    {
-        let lhs = &self.0;
-        let rhs = &other.0;
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = if self.index == self.elems.len() {
+            0
+        } else {
+            self.index + 1
+        };
+        self.elems.get(next)
+    }
 
-        let ans_cap = lhs.len().min(rhs.len());
-        let mut ans = Vec::with_capacity(ans_cap);
+    /// Returns the element before the current one, or the last element at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let prev = self.index.checked_sub(1)?;
+        self.elems.get(prev)
+    }
 
-        unsafe {
-            let p1 = lhs.as_ptr();
-            let p2 = rhs.as_ptr();
-            let p3 = ans.as_mut_ptr();
-            let e1 = p1.add(lhs.len());
-            let e2 = p2.add(rhs.len());
+    /// Moves to the next element, or from the ghost position to the first element.
+    #[inline]
+    pub fn move_next(&mut self) {
+        if self.index == self.elems.len() {
+            self.index = 0;
+        } else {
+            self.index += 1;
+        }
+    }
 
-            let end = raw_intersection_copied(p1, p2, p3, e1, e2);
-            let cnt = end.offset_from(p3) as usize;
-            ans.set_len(cnt)
+    /// Moves to the previous element, or from the first element to the ghost position.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        if self.index == 0 {
+            self.index = self.elems.len();
+        } else {
+            self.index -= 1;
         }
+    }
+}
+
+/// A cursor over a `VecSet` with editing operations.
+pub struct CursorMut<'a, T, C = Natural> {
+    elems: &'a mut Vec<T>,
+    cmp: &'a C,
+    index: usize,
+}
 
-        Self(ans)
+impl<T, C> CursorMut<'_, T, C> {
+    /// Returns the index of the current element, or `None` at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> Option<usize> {
+        (self.index < self.elems.len()).then_some(self.index)
     }
 
+    /// Returns the current element, or `None` at the ghost position.
     #[inline]
-    pub fn difference_copied_inplace(&mut self, other: &Self)
-    where
-        T: Copy,
-    {
-        let lhs = &mut self.0;
-        let rhs = &other.0;
+    #[must_use]
+    pub fn current(&self) -> Option<&T> {
+        self.elems.get(self.index)
+    }
 
-        let ans_cap = lhs.len();
-        lhs.reserve(ans_cap);
+    /// Returns the element after the current one, or the first element at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn peek_next(&self) -> Option<&T> {
+        self.as_cursor().peek_next()
+    }
 
-        unsafe {
-            let p1 = lhs.as_ptr();
-            let p2 = rhs.as_ptr();
-            let p3 = lhs.as_mut_ptr().add(lhs.len());
-            let e1 = p1.add(lhs.len());
-            let e2 = p2.add(rhs.len());
+    /// Returns the element before the current one, or the last element at the ghost position.
+    #[inline]
+    #[must_use]
+    pub fn peek_prev(&self) -> Option<&T> {
+        self.as_cursor().peek_prev()
+    }
 
-            let end = raw_difference_copied(p1, p2, p3, e1, e2);
+    /// Moves to the next element, or from the ghost position to the first element.
+    #[inline]
+    pub fn move_next(&mut self) {
+        if self.index == self.elems.len() {
+            self.index = 0;
+        } else {
+            self.index += 1;
+        }
+    }
 
-            let dst = lhs.as_mut_ptr();
-            let src = dst.add(lhs.len());
-            let cnt = end.offset_from(src) as usize;
-            ptr::copy(src, dst, cnt);
-            lhs.set_len(cnt)
+    /// Moves to the previous element, or from the first element to the ghost position.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        if self.index == 0 {
+            self.index = self.elems.len();
+        } else {
+            self.index -= 1;
         }
     }
-}
 
-impl<T: Ord> From<Vec<T>> for VecSet<T> {
+    /// Returns a read-only cursor at the same position.
     #[inline]
-    fn from(v: Vec<T>) -> Self {
-        Self::from_vec(v)
+    #[must_use]
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            elems: self.elems,
+            index: self.index,
+        }
     }
-}
 
-impl<T: Ord> FromIterator<T> for VecSet<T> {
+    /// Removes the current element and moves the cursor to the next one.
     #[inline]
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        Self::from_vec(iter.into_iter().collect())
+    pub fn remove_current(&mut self) -> Option<T> {
+        (self.index < self.elems.len()).then(|| self.elems.remove(self.index))
     }
 }
 
-impl<T> Default for VecSet<T> {
+impl<T, C: Comparator<T>> CursorMut<'_, T, C> {
+    /// Inserts an element immediately before the current one.
+    ///
+    /// Fails if `value` does not sort strictly between the neighbouring elements.
     #[inline]
-    fn default() -> Self {
-        Self::new()
+    pub fn insert_before(&mut self, value: T) -> Result<(), UnorderedKeyError> {
+        let cmp = self.cmp;
+        if self
+            .peek_prev()
+            .is_some_and(|prev| cmp.compare(prev, &value).is_ge())
+        {
+            return Err(UnorderedKeyError);
+        }
+        if self
+            .current()
+            .is_some_and(|cur| cmp.compare(cur, &value).is_le())
+        {
+            return Err(UnorderedKeyError);
+        }
+        self.elems.insert(self.index, value);
+        self.index += 1;
+        Ok(())
     }
-}
 
-impl<T: fmt::Debug> fmt::Debug for VecSet<T> {
+    /// Inserts an element immediately after the current one, or at the front
+    /// at the ghost position.
+    ///
+    /// Fails if `value` does not sort strictly between the neighbouring elements.
     #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_set().entries(self.0.iter()).finish()
+    pub fn insert_after(&mut self, value: T) -> Result<(), UnorderedKeyError> {
+        let cmp = self.cmp;
+        if self
+            .current()
+            .is_some_and(|cur| cmp.compare(cur, &value).is_ge())
+        {
+            return Err(UnorderedKeyError);
+        }
+        if self
+            .peek_next()
+            .is_some_and(|next| cmp.compare(next, &value).is_le())
+        {
+            return Err(UnorderedKeyError);
+        }
+        if self.index == self.elems.len() {
+            self.elems.insert(0, value);
+            self.index += 1;
+        } else {
+            self.elems.insert(self.index + 1, value);
+        }
+        Ok(())
     }
 }
 
-pub struct Iter<'a, T>(slice::Iter<'a, T>);
+/// An iterator over the elements in either of two sets, created by [`VecSet::union_iter`].
+pub struct Union<'a, T, C = Natural>(MergeWalk<'a, T, T, Whole, C>);
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<'a, T, C: Comparator<T>> Iterator for Union<'a, T, C> {
     type Item = &'a T;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.0.next().map(|item| match item {
+            EitherOrBoth::Left(x) | EitherOrBoth::Both(x, _) => x,
+            EitherOrBoth::Right(y) => y,
+        })
     }
 
     #[inline]
@@ -271,72 +1889,111 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a VecSet<T> {
+impl<T, C: Comparator<T>> FusedIterator for Union<'_, T, C> {}
+
+impl<T, C> Clone for Union<'_, T, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the elements in both of two sets, created by
+/// [`VecSet::intersection_iter`].
+pub struct Intersection<'a, T, C = Natural>(MergeWalk<'a, T, T, Whole, C>);
+
+impl<'a, T, C: Comparator<T>> Iterator for Intersection<'a, T, C> {
     type Item = &'a T;
 
-    type IntoIter = Iter<'a, T>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_both().map(|(x, _)| x)
+    }
 
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l, r) = self.0.remaining();
+        (0, Some(l.min(r)))
     }
 }
 
-pub struct IterMut<'a, T>(slice::IterMut<'a, T>);
+impl<T, C: Comparator<T>> FusedIterator for Intersection<'_, T, C> {}
 
-impl<'a, T> Iterator for IterMut<'a, T> {
-    type Item = &'a mut T;
+impl<T, C> Clone for Intersection<'_, T, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// An iterator over the elements only in the first of two sets, created by
+/// [`VecSet::difference_iter`].
+pub struct Difference<'a, T, C = Natural>(MergeWalk<'a, T, T, Whole, C>);
+
+impl<'a, T, C: Comparator<T>> Iterator for Difference<'a, T, C> {
+    type Item = &'a T;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        loop {
+            if let (x, None) = self.0.next_left()? {
+                return Some(x);
+            }
+        }
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        let (l, r) = self.0.remaining();
+        (l.saturating_sub(r), Some(l))
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut VecSet<T> {
-    type Item = &'a mut T;
-
-    type IntoIter = IterMut<'a, T>;
+impl<T, C: Comparator<T>> FusedIterator for Difference<'_, T, C> {}
 
+impl<T, C> Clone for Difference<'_, T, C> {
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
     }
 }
 
-pub struct IntoIter<T>(vec::IntoIter<T>);
+/// An iterator over the elements in exactly one of two sets, created by
+/// [`VecSet::symmetric_difference_iter`].
+pub struct SymmetricDifference<'a, T, C = Natural>(MergeWalk<'a, T, T, Whole, C>);
 
-impl<T> Iterator for IntoIter<T> {
-    type Item = T;
+impl<'a, T, C: Comparator<T>> Iterator for SymmetricDifference<'a, T, C> {
+    type Item = &'a T;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        loop {
+            match self.0.next()? {
+                EitherOrBoth::Left(x) => return Some(x),
+                EitherOrBoth::Right(y) => return Some(y),
+                EitherOrBoth::Both(..) => {}
+            }
+        }
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.0.size_hint()
+        let (l, r) = self.0.remaining();
+        (l.abs_diff(r), l.checked_add(r))
     }
 }
 
-impl<T> IntoIterator for VecSet<T> {
-    type Item = T;
-
-    type IntoIter = IntoIter<T>;
+impl<T, C: Comparator<T>> FusedIterator for SymmetricDifference<'_, T, C> {}
 
+impl<T, C> Clone for SymmetricDifference<'_, T, C> {
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        IntoIter(self.0.into_iter())
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
     }
 }
 
-unsafe fn raw_union_copied<T: Copy + Ord>(
+unsafe fn raw_union_copied<T: Copy, C: Comparator<T>>(
+    cmp: &C,
     mut p1: *const T,
     mut p2: *const T,
     mut p3: *mut T,
@@ -344,7 +2001,7 @@ unsafe fn raw_union_copied<T: Copy + Ord>(
     e2: *const T,
 ) -> *mut T {
     while p1 < e1 && p2 < e2 {
-        match Ord::cmp(&*p1, &*p2) {
+        match cmp.compare(&*p1, &*p2) {
             Ordering::Less => {
                 ptr::copy_nonoverlapping(p1, p3, 1);
                 p1 = p1.add(1);
@@ -374,7 +2031,8 @@ unsafe fn raw_union_copied<T: Copy + Ord>(
     p3
 }
 
-unsafe fn raw_intersection_copied<T: Copy + Ord>(
+unsafe fn raw_intersection_copied<T: Copy, C: Comparator<T>>(
+    cmp: &C,
     mut p1: *const T,
     mut p2: *const T,
     mut p3: *mut T,
@@ -382,7 +2040,7 @@ unsafe fn raw_intersection_copied<T: Copy + Ord>(
     e2: *const T,
 ) -> *mut T {
     while p1 < e1 && p2 < e2 {
-        match Ord::cmp(&*p1, &*p2) {
+        match cmp.compare(&*p1, &*p2) {
             Ordering::Less => {
                 p1 = p1.add(1);
             }
@@ -400,7 +2058,40 @@ unsafe fn raw_intersection_copied<T: Copy + Ord>(
     p3
 }
 
-unsafe fn raw_difference_copied<T: Copy + Ord>(
+unsafe fn raw_difference_copied<T: Copy, C: Comparator<T>>(
+    cmp: &C,
+    mut p1: *const T,
+    mut p2: *const T,
+    mut p3: *mut T,
+    e1: *const T,
+    e2: *const T,
+) -> *mut T {
+    while p1 < e1 && p2 < e2 {
+        match cmp.compare(&*p1, &*p2) {
+            Ordering::Less => {
+                ptr::copy_nonoverlapping(p1, p3, 1);
+                p1 = p1.add(1);
+                p3 = p3.add(1);
+            }
+            Ordering::Greater => {
+                p2 = p2.add(1);
+            }
+            Ordering::Equal => {
+                p1 = p1.add(1);
+                p2 = p2.add(1);
+            }
+        }
+    }
+    if p1 < e1 {
+        let cnt = e1.offset_from(p1) as usize;
+        ptr::copy_nonoverlapping(p1, p3, cnt);
+        p3 = p3.add(cnt);
+    }
+    p3
+}
+
+unsafe fn raw_symmetric_difference_copied<T: Copy, C: Comparator<T>>(
+    cmp: &C,
     mut p1: *const T,
     mut p2: *const T,
     mut p3: *mut T,
@@ -408,14 +2099,16 @@ unsafe fn raw_difference_copied<T: Copy + Ord>(
     e2: *const T,
 ) -> *mut T {
     while p1 < e1 && p2 < e2 {
-        match Ord::cmp(&*p1, &*p2) {
+        match cmp.compare(&*p1, &*p2) {
             Ordering::Less => {
                 ptr::copy_nonoverlapping(p1, p3, 1);
                 p1 = p1.add(1);
                 p3 = p3.add(1);
             }
             Ordering::Greater => {
+                ptr::copy_nonoverlapping(p2, p3, 1);
                 p2 = p2.add(1);
+                p3 = p3.add(1);
             }
             Ordering::Equal => {
                 p1 = p1.add(1);
@@ -428,6 +2121,11 @@ unsafe fn raw_difference_copied<T: Copy + Ord>(
         ptr::copy_nonoverlapping(p1, p3, cnt);
         p3 = p3.add(cnt);
     }
+    if p2 < e2 {
+        let cnt = e2.offset_from(p2) as usize;
+        ptr::copy_nonoverlapping(p2, p3, cnt);
+        p3 = p3.add(cnt);
+    }
     p3
 }
 
@@ -435,12 +2133,308 @@ unsafe fn raw_difference_copied<T: Copy + Ord>(
 mod tests {
     use super::*;
 
+    use alloc::string::String;
+
     #[test]
     fn from_vec() {
         let s = VecSet::<u64>::from_vec(vec![1, 4, 3, 2, 5, 7, 9, 2, 4, 6, 7, 8, 0]);
         assert_eq!(s.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9])
     }
 
+    #[test]
+    fn range() {
+        let s = VecSet::<u64>::from_iter([1, 3, 5, 7]);
+        assert!(s.range(..).eq(&[1, 3, 5, 7]));
+        assert!(s.range(2..7).eq(&[3, 5]));
+        assert!(s.range(3..=7).eq(&[3, 5, 7]));
+        assert!(s
+            .range((Bound::Excluded(1), Bound::Excluded(7)))
+            .eq(&[3, 5]));
+        assert!(s.range(..0).eq(&[]));
+        assert!(s.range(4..).rev().eq(&[7, 5]));
+    }
+
+    #[test]
+    fn navigation() {
+        let mut s = VecSet::<u64>::from_iter([2, 4, 6]);
+
+        assert_eq!(s.first(), Some(&2));
+        assert_eq!(s.last(), Some(&6));
+        assert_eq!(s.get(&4), Some(&4));
+        assert_eq!(s.get(&5), None);
+
+        assert_eq!(s.lower_bound(&1), (0, Some(&2)));
+        assert_eq!(s.lower_bound(&4), (1, Some(&4)));
+        assert_eq!(s.lower_bound(&5), (2, Some(&6)));
+        assert_eq!(s.lower_bound(&7), (3, None));
+        assert_eq!(s.upper_bound(&1), (0, Some(&2)));
+        assert_eq!(s.upper_bound(&4), (2, Some(&6)));
+        assert_eq!(s.upper_bound(&6), (3, None));
+
+        assert_eq!(s.floor(&1), None);
+        assert_eq!(s.floor(&4), Some(&4));
+        assert_eq!(s.floor(&5), Some(&4));
+        assert_eq!(s.ceiling(&4), Some(&4));
+        assert_eq!(s.ceiling(&5), Some(&6));
+        assert_eq!(s.ceiling(&7), None);
+        assert_eq!(s.predecessor(&2), None);
+        assert_eq!(s.predecessor(&4), Some(&2));
+        assert_eq!(s.successor(&4), Some(&6));
+        assert_eq!(s.successor(&6), None);
+
+        assert_eq!(s.pop_first(), Some(2));
+        assert_eq!(s.pop_last(), Some(6));
+        assert_eq!(s.pop_last(), Some(4));
+        assert_eq!(s.pop_first(), None);
+        assert_eq!(s.first(), None);
+    }
+
+    #[test]
+    fn remove_range() {
+        let mut s = VecSet::<u64>::from_iter(1..=6);
+        s.remove_range(3..5);
+        assert_eq!(s.as_slice(), &[1, 2, 5, 6]);
+        s.remove_range(..=1);
+        assert_eq!(s.as_slice(), &[2, 5, 6]);
+        s.remove_range(0..);
+        assert!(s.is_empty());
+
+        let mut s = VecSet::<u64>::from_iter(1..=6);
+        assert!(s.drain_range(2..=4).eq([2, 3, 4]));
+        assert_eq!(s.as_slice(), &[1, 5, 6]);
+        assert!(s.drain_range(5..).rev().eq([6, 5]));
+        assert_eq!(s.as_slice(), &[1]);
+
+        let mut s = VecSet::<u64>::from_iter(1..=6);
+        s.retain_range(2..5);
+        assert_eq!(s.as_slice(), &[2, 3, 4]);
+        s.retain_range(7..);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn as_mut_slice() {
+        let mut s = VecSet::<u64>::from_iter([1, 2, 3, 4]);
+        for x in s.as_mut_slice().iter_mut() {
+            *x *= 2;
+        }
+        assert_eq!(s.as_slice(), &[2, 4, 6, 8]);
+
+        {
+            let mut guard = s.as_mut_slice();
+            guard[0] = 9;
+            guard[3] = 4;
+        }
+        assert_eq!(s.as_slice(), &[4, 6, 9]);
+
+        mem::forget(s.as_mut_slice());
+        assert_eq!(s.as_slice(), &[4, 6, 9]);
+    }
+
+    #[test]
+    fn as_mut_slice_panic() {
+        extern crate std;
+
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut s = VecSet::<u64>::from_iter([1, 2, 3]);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut guard = s.as_mut_slice();
+            guard[0] = 5;
+            panic!("boom");
+        }));
+        assert!(result.is_err());
+        assert_eq!(s.as_slice(), &[2, 3, 5]);
+    }
+
+    #[test]
+    fn iterators() {
+        let s = VecSet::<u64>::from_iter([3, 1, 2]);
+
+        assert_eq!(s.iter().len(), 3);
+        assert!(s.iter().rev().eq(&[3, 2, 1]));
+        assert!(s.iter().clone().eq(&s));
+        assert_eq!(s.range(2..).len(), 2);
+
+        let mut iter = s.into_iter();
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.len(), 2);
+        assert!(iter.clone().eq([1, 2]));
+        assert!(iter.by_ref().eq([1, 2]));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn extend() {
+        let mut s = VecSet::<u64>::from_iter([2, 4, 6]);
+        s.extend([5, 1, 4, 7, 1]);
+        assert_eq!(s.as_slice(), &[1, 2, 4, 5, 6, 7]);
+        s.extend(&[0, 8]);
+        assert_eq!(s.as_slice(), &[0, 1, 2, 4, 5, 6, 7, 8]);
+
+        let mut s = VecSet::<u64>::new();
+        s.extend(Some(1));
+        s.extend(None::<u64>);
+        assert_eq!(s.as_slice(), &[1]);
+    }
+
+    #[test]
+    fn apply_batch() {
+        let mut s = VecSet::<u64>::from_iter([1, 2, 3, 4]);
+        let outcomes = s.apply_batch([
+            BatchOp::Remove(3),
+            BatchOp::Insert(2),
+            BatchOp::Upsert(4),
+            BatchOp::Insert(6),
+            BatchOp::Remove(5),
+            BatchOp::Remove(6),
+            BatchOp::Upsert(0),
+        ]);
+        assert_eq!(
+            outcomes,
+            [
+                BatchOutcome::Removed(3),
+                BatchOutcome::Occupied(2),
+                BatchOutcome::Replaced(4),
+                BatchOutcome::Inserted,
+                BatchOutcome::NotFound,
+                BatchOutcome::Removed(6),
+                BatchOutcome::Inserted,
+            ]
+        );
+        assert_eq!(s.as_slice(), &[0, 1, 2, 4]);
+    }
+
+    #[test]
+    fn apply_batch_panic() {
+        extern crate std;
+
+        use core::cell::Cell;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let armed = Cell::new(false);
+        let cmp = |lhs: &u8, rhs: &u8| {
+            assert!(!armed.get() || (*lhs != 3 && *rhs != 3), "boom");
+            lhs.cmp(rhs)
+        };
+        let mut s = VecSet::from_vec_with_comparator((0..10).collect(), cmp);
+        armed.set(true);
+
+        let ops = [BatchOp::Remove(2), BatchOp::Upsert(7), BatchOp::Insert(12)];
+        let result = catch_unwind(AssertUnwindSafe(|| s.apply_batch(ops)));
+        assert!(result.is_err());
+        assert_eq!(s.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 12]);
+    }
+
+    #[test]
+    fn retain() {
+        let mut s = VecSet::<u64>::from_iter(1..=6);
+        s.retain(|&x| x % 3 != 0);
+        assert_eq!(s.as_slice(), &[1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut s = VecSet::<u64>::from_iter(1..=6);
+        assert!(s.extract_if(|&x| x % 2 == 0).eq([2, 4, 6]));
+        assert_eq!(s.as_slice(), &[1, 3, 5]);
+
+        let mut s = VecSet::<u64>::from_iter(1..=6);
+        let mut iter = s.extract_if(|&x| x > 1);
+        assert_eq!(iter.next(), Some(2));
+        drop(iter);
+        assert_eq!(s.as_slice(), &[1, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn cursor() {
+        let s = VecSet::<u64>::from_iter([1, 3, 5]);
+        let mut c = s.lower_bound_cursor(Bound::Excluded(&3));
+        assert_eq!(c.current(), Some(&5));
+        assert_eq!(c.peek_prev(), Some(&3));
+        assert_eq!(c.peek_next(), None);
+        c.move_next();
+        assert_eq!(c.index(), None);
+        assert_eq!(c.peek_next(), Some(&1));
+        c.move_prev();
+        assert_eq!(c.index(), Some(2));
+        assert_eq!(
+            s.upper_bound_cursor(Bound::Included(&4)).current(),
+            Some(&3)
+        );
+        assert_eq!(s.upper_bound_cursor(Bound::Excluded(&1)).index(), None);
+
+        let mut s = VecSet::<u64>::from_iter([2, 4, 6]);
+        let mut c = s.upper_bound_cursor_mut(Bound::Included(&4));
+        assert_eq!(c.insert_after(6), Err(UnorderedKeyError));
+        assert_eq!(c.insert_after(5), Ok(()));
+        assert_eq!(c.insert_before(2), Err(UnorderedKeyError));
+        assert_eq!(c.insert_before(3), Ok(()));
+        assert_eq!(c.remove_current(), Some(4));
+        assert_eq!(c.current(), Some(&5));
+        assert_eq!(s.as_slice(), &[2, 3, 5, 6]);
+    }
+
+    #[test]
+    fn positional() {
+        let mut s = VecSet::<u64>::from_iter([10, 20, 30, 40]);
+        assert_eq!(s.get_index(2), Some(&30));
+        assert_eq!(s.index_of(&40), Some(3));
+        assert_eq!(s.index_of(&35), None);
+        assert_eq!(s.rank(&35), 3);
+        assert_eq!(s.median(), Some(&20));
+        assert_eq!(s.quantile(0.5), Some(&20));
+        assert_eq!(s.quantile(1.0), Some(&40));
+        assert_eq!(s.quantile(f64::NAN), None);
+        assert_eq!(s.quantile(2.0), None);
+        assert_eq!(s.remove_index(0), Some(10));
+        assert_eq!(s.median(), Some(&30));
+    }
+
+    #[test]
+    fn custom_comparator() {
+        let total = |lhs: &f64, rhs: &f64| lhs.total_cmp(rhs);
+        let s = VecSet::from_vec_with_comparator(vec![2.0, f64::NAN, -0.0, 0.0, 2.0], total);
+        assert_eq!(s.len(), 4);
+        assert!(s.contains(&f64::NAN));
+        assert_eq!(s.first(), Some(&-0.0));
+        assert_eq!(s.range(-1.0..1.0).count(), 2);
+
+        #[derive(Clone, Default)]
+        struct ByRank([u8; 4]);
+
+        impl Comparator<u8> for ByRank {
+            fn compare(&self, key: &u8, query: &u8) -> Ordering {
+                self.0[usize::from(*key)].cmp(&self.0[usize::from(*query)])
+            }
+        }
+
+        let rank = ByRank([2, 0, 3, 1]);
+        let lhs = VecSet::from_vec_with_comparator(vec![0, 1, 2], rank.clone());
+        let rhs = VecSet::from_vec_with_comparator(vec![3, 0], rank);
+        assert_eq!(lhs.as_slice(), &[1, 0, 2]);
+        assert_eq!((&lhs | &rhs).as_slice(), &[1, 3, 0, 2]);
+        assert_eq!((&lhs & &rhs).as_slice(), &[0]);
+        assert!(lhs.union_iter(&rhs).eq(&[1, 3, 0, 2]));
+        assert!(lhs.clone().union(rhs.clone()).is_superset(&rhs));
+
+        let mut s = lhs;
+        s.as_mut_slice().fill(3);
+        assert_eq!(s.as_slice(), &[3]);
+    }
+
+    #[test]
+    fn search_by() {
+        let s = VecSet::from_iter([b"ab".as_slice(), b"abc", b"abd", b"b"]);
+        let prefix = |p: &'static [u8]| move |x: &&[u8]| x[..p.len().min(x.len())].cmp(p);
+        assert_eq!(s.binary_search_by(|x| x.cmp(&b"abd".as_slice())), Ok(2));
+        assert_eq!(s.get_by(prefix(b"b")), Some(&b"b".as_slice()));
+        assert_eq!(s.partition_point(|x| x[0] == b'a'), 3);
+        assert!(s.range_by(prefix(b"abc")).eq([b"abc"]));
+        assert_eq!(s.range_by(prefix(b"ab")).len(), 3);
+        assert_eq!(s.range_by(prefix(b"c")).len(), 0);
+    }
+
     #[test]
     fn union() {
         {
@@ -457,6 +2451,209 @@ mod tests {
         }
     }
 
+    #[test]
+    fn symmetric_difference() {
+        let s1 = VecSet::<u64>::from_iter([1, 2, 3, 5]);
+        let s2 = VecSet::<u64>::from_iter([2, 4, 5, 6]);
+        assert_eq!(
+            s1.symmetric_difference_copied(&s2).as_slice(),
+            &[1, 3, 4, 6]
+        );
+        assert_eq!(
+            s1.clone().symmetric_difference(s2.clone()).as_slice(),
+            &[1, 3, 4, 6]
+        );
+        assert_eq!(s1.symmetric_difference_copied(&s1).as_slice(), &[]);
+        assert_eq!(s1.difference_copied(&s2).as_slice(), &[1, 3]);
+        assert_eq!(s2.difference_copied(&s1).as_slice(), &[4, 6]);
+
+        let mut s3 = s1.clone();
+        s3.symmetric_difference_copied_inplace(&s2);
+        assert_eq!(s3.as_slice(), &[1, 3, 4, 6]);
+
+        let mut s3 = s1.clone();
+        s3.intersection_inplace(&s2);
+        assert_eq!(s3.as_slice(), &[2, 5]);
+        s3.intersection_inplace(&VecSet::new());
+        assert!(s3.is_empty());
+    }
+
+    #[test]
+    fn lazy_set_ops() {
+        let s1 = VecSet::<u64>::from_iter([1, 2, 3, 5]);
+        let s2 = VecSet::<u64>::from_iter([2, 4, 5, 6]);
+        let empty = VecSet::<u64>::new();
+
+        assert!(s1.union_iter(&s2).eq(&[1, 2, 3, 4, 5, 6]));
+        assert!(s1.intersection_iter(&s2).eq(&[2, 5]));
+        assert!(s1.difference_iter(&s2).eq(&[1, 3]));
+        assert!(s2.difference_iter(&s1).eq(&[4, 6]));
+        assert!(s1.symmetric_difference_iter(&s2).eq(&[1, 3, 4, 6]));
+
+        assert!(s1.union_iter(&empty).eq(&s1));
+        assert!(empty.union_iter(&s1).eq(&s1));
+        assert_eq!(s1.intersection_iter(&empty).count(), 0);
+        assert!(s1.difference_iter(&empty).eq(&s1));
+        assert!(empty.symmetric_difference_iter(&s2).eq(&s2));
+        assert_eq!(
+            empty.symmetric_difference_iter(&s2).size_hint(),
+            (4, Some(4))
+        );
+
+        let union = s1.union_iter(&s2);
+        assert_eq!(union.size_hint(), (4, Some(8)));
+        assert_eq!(union.clone().count(), 6);
+        assert_eq!(s1.intersection_iter(&s2).size_hint(), (0, Some(4)));
+    }
+
+    #[test]
+    fn operators() {
+        let s1 = VecSet::<u64>::from_iter([1, 2, 3, 5]);
+        let s2 = VecSet::<u64>::from_iter([2, 4, 5, 6]);
+
+        assert_eq!((&s1 | &s2).as_slice(), &[1, 2, 3, 4, 5, 6]);
+        assert_eq!((&s1 & &s2).as_slice(), &[2, 5]);
+        assert_eq!((&s1 - &s2).as_slice(), &[1, 3]);
+        assert_eq!((&s1 ^ &s2).as_slice(), &[1, 3, 4, 6]);
+
+        let mut s3 = s1.clone();
+        s3 |= &s2;
+        assert_eq!(s3.as_slice(), &[1, 2, 3, 4, 5, 6]);
+        s3 -= &s1;
+        assert_eq!(s3.as_slice(), &[4, 6]);
+        s3 ^= &s2;
+        assert_eq!(s3.as_slice(), &[2, 5]);
+        s3 &= &s1;
+        assert_eq!(s3.as_slice(), &[2, 5]);
+    }
+
+    #[test]
+    fn ordering() {
+        let a = VecSet::<u32>::from_iter([1, 2]);
+        let b = VecSet::<u32>::from_iter([1, 3]);
+        let c = VecSet::<u32>::from_iter([1, 2, 3]);
+        assert!(a < b);
+        assert!(a < c);
+        assert!(c < b);
+        assert!(VecSet::new() < a);
+
+        let nested = VecSet::from_iter([b.clone(), a.clone(), c.clone(), a.clone()]);
+        assert_eq!(nested.as_slice(), &[a.clone(), c, b]);
+        assert!(nested.contains(&a));
+    }
+
+    #[test]
+    fn predicates() {
+        let s1 = VecSet::<u64>::from_iter([1, 2, 3, 5]);
+        let s2 = VecSet::<u64>::from_iter([2, 5]);
+        let s3 = VecSet::<u64>::from_iter([4, 6]);
+        let empty = VecSet::<u64>::new();
+
+        assert!(s2.is_subset(&s1));
+        assert!(!s1.is_subset(&s2));
+        assert!(s1.is_superset(&s2));
+        assert!(s1.is_superset(&empty));
+        assert!(empty.is_subset(&s3));
+        assert!(!s3.is_subset(&s1));
+        assert!(s1.is_disjoint(&s3));
+        assert!(!s1.is_disjoint(&s2));
+        assert!(empty.is_disjoint(&empty));
+
+        let set = |v: &[&str]| v.iter().map(|s| String::from(*s)).collect::<VecSet<_>>();
+        let s4 = set(&["a", "b", "c"]);
+        assert!(s4.contains_all(&set(&["a", "c"])));
+        assert!(!s4.contains_all(&set(&["a", "d"])));
+        assert!(!s4.contains_all(&set(&["a", "b", "c", "d"])));
+        assert!(s4.contains_any(&set(&["c", "d"])));
+        assert!(!s4.contains_any(&set(&["d"])));
+    }
+
+    #[test]
+    fn set_ops_cloned() {
+        let set = |v: &[&str]| v.iter().map(|s| String::from(*s)).collect::<VecSet<_>>();
+        let s1 = set(&["a", "b", "c", "e"]);
+        let s2 = set(&["b", "d", "e", "f"]);
+
+        assert_eq!(s1.union_cloned(&s2), set(&["a", "b", "c", "d", "e", "f"]));
+        assert_eq!(s1.intersection_cloned(&s2), set(&["b", "e"]));
+        assert_eq!(s1.difference_cloned(&s2), set(&["a", "c"]));
+        assert_eq!(s2.difference_cloned(&s1), set(&["d", "f"]));
+
+        let mut s3 = s1.clone();
+        s3.union_cloned_inplace(&s2);
+        assert_eq!(s3, set(&["a", "b", "c", "d", "e", "f"]));
+
+        let mut s3 = s1.clone();
+        s3.difference_inplace(&s2);
+        assert_eq!(s3, set(&["a", "c"]));
+        s3.difference_inplace(&VecSet::new());
+        assert_eq!(s3, set(&["a", "c"]));
+
+        assert_eq!(s1.clone().union(s2.clone()), s1.union_cloned(&s2));
+        assert_eq!(s1.clone().intersection(s2.clone()), set(&["b", "e"]));
+        assert_eq!(s1.difference(s2), set(&["a", "c"]));
+
+        let cmp = |lhs: &String, rhs: &String| lhs.to_lowercase().cmp(&rhs.to_lowercase());
+        let s1 = VecSet::from_vec_with_comparator(vec!["A".into(), "b".into()], cmp);
+        let s2 = VecSet::from_vec_with_comparator(vec!["a".into(), "c".into()], cmp);
+        assert!(s1.union_cloned(&s2).iter().eq(["A", "b", "c"]));
+        let mut s3 = s1.clone();
+        s3.union_cloned_inplace(&s2);
+        assert!(s3.iter().eq(["A", "b", "c"]));
+        assert!(s1.union(s2).iter().eq(["A", "b", "c"]));
+    }
+
+    #[test]
+    fn set_ops_panic() {
+        extern crate std;
+
+        use alloc::rc::Rc;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        struct Bomb {
+            val: u8,
+            _token: Rc<()>,
+        }
+
+        impl PartialEq for Bomb {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for Bomb {}
+
+        impl PartialOrd for Bomb {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Bomb {
+            fn cmp(&self, other: &Self) -> Ordering {
+                assert!(self.val != 3 && other.val != 3);
+                self.val.cmp(&other.val)
+            }
+        }
+
+        let token = Rc::new(());
+        let set = |r: core::ops::Range<u8>| -> VecSet<Bomb> {
+            let bomb = |val| Bomb {
+                val,
+                _token: token.clone(),
+            };
+            VecSet(r.map(bomb).collect(), Natural)
+        };
+
+        let result = catch_unwind(AssertUnwindSafe(|| set(0..5).union(set(2..7))));
+        assert!(result.is_err());
+        let result = catch_unwind(AssertUnwindSafe(|| set(0..5).intersection(set(2..7))));
+        assert!(result.is_err());
+        let result = catch_unwind(AssertUnwindSafe(|| set(0..5).difference(set(2..7))));
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&token), 1);
+    }
+
     #[test]
     fn intersection() {
         let s1 = VecSet::<u64>::from_vec(vec![1, 2, 3, 5]);
@@ -494,17 +2691,22 @@ mod serde_impl {
 
     use serde::{Deserialize, Serialize};
 
-    impl<'de, T: Ord + Deserialize<'de>> Deserialize<'de> for VecSet<T> {
+    impl<'de, T, C> Deserialize<'de> for VecSet<T, C>
+    where
+        T: Deserialize<'de>,
+        C: Comparator<T> + Default,
+    {
         #[inline]
-        fn deserialize<D>(deserializer: D) -> Result<VecSet<T>, D::Error>
+        fn deserialize<D>(deserializer: D) -> Result<VecSet<T, C>, D::Error>
         where
             D: ::serde::de::Deserializer<'de>,
         {
-            <Vec<T>>::deserialize(deserializer).map(VecSet::from_vec)
+            let elems = <Vec<T>>::deserialize(deserializer)?;
+            Ok(VecSet::from_vec_with_comparator(elems, C::default()))
         }
     }
 
-    impl<T: Serialize> Serialize for VecSet<T> {
+    impl<T: Serialize, C> Serialize for VecSet<T, C> {
         #[inline]
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where